@@ -1,13 +1,18 @@
 use std::{
+  cell::{OnceCell, Ref, RefCell},
   cmp,
   cmp::Ordering,
+  collections::{HashMap, VecDeque},
   env,
-  fmt::Debug,
+  fmt::{Debug, Display, Write},
   fs,
-  fs::FileType,
-  io::Error,
-  ops::Deref,
-  path::{Path, PathBuf},
+  fs::{DirEntry, FileType, Metadata},
+  io::{Error, Read},
+  ops::{Deref, Range},
+  path::{self, Path, PathBuf},
+  sync::{mpsc, Arc},
+  thread,
+  time::SystemTime,
 };
 
 use egui::{
@@ -35,621 +40,4845 @@ pub enum DialogType {
   SaveFile,
 }
 
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// What the file list is sorted by. Folders are always listed before files.
+pub enum SortKey {
+  /// Sort by file name.
+  #[default]
+  Name,
+  /// Sort by file size, folders count as empty.
+  Size,
+  /// Sort by modification time.
+  Modified,
+}
+
+impl SortKey {
+  /// All sort keys, in the order they appear in the header.
+  const ALL: [SortKey; 3] = [SortKey::Name, SortKey::Size, SortKey::Modified];
+
+  fn label(self, labels: &FileDialogLabels) -> &'static str {
+    match self {
+      SortKey::Name => labels.name_header,
+      SortKey::Size => labels.size_header,
+      SortKey::Modified => labels.modified_header,
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+/// How modification times are displayed.
+pub enum DateDisplay {
+  /// Formatted with the date format, e.g. "2024-05-01 12:30".
+  #[default]
+  Absolute,
+  /// Relative to now, e.g. "3 days ago". Times older than four weeks are shown as absolute.
+  Relative,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+/// How the current path is shown at the top of the dialog.
+pub enum PathBarMode {
+  /// A text field with the path.
+  #[default]
+  Editable,
+  /// A button for each folder of the path, clicking one opens it. Clicking the empty part of the
+  /// bar shows the text field until it loses focus.
+  Breadcrumb,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// How the files are laid out in the dialog.
+pub enum ViewMode {
+  /// One file name per row.
+  #[default]
+  List,
+  /// One file per row with name, size and modification time in columns.
+  Details,
+  /// Tiles with a thumbnail for images (with the `image` feature) and an icon otherwise.
+  Grid { columns: usize },
+}
+
+impl ViewMode {
+  /// All view modes, in the order they appear in the toolbar.
+  const ALL: [ViewMode; 3] = [
+    ViewMode::List,
+    ViewMode::Details,
+    ViewMode::Grid { columns: 4 },
+  ];
+
+  fn label(self, labels: &FileDialogLabels) -> &'static str {
+    match self {
+      ViewMode::List => labels.list_view_button,
+      ViewMode::Details => labels.details_view_button,
+      ViewMode::Grid { .. } => labels.grid_view_button,
+    }
+  }
+
+  fn shortcut(self) -> Key {
+    match self {
+      ViewMode::List => Key::Num1,
+      ViewMode::Details => Key::Num2,
+      ViewMode::Grid { .. } => Key::Num3,
+    }
+  }
+
+  /// Returns true, if both are the same mode, ignoring their settings.
+  fn same_mode(self, other: ViewMode) -> bool {
+    std::mem::discriminant(&self) == std::mem::discriminant(&other)
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// Settings of a dialog that can be stored, e.g. in the app's config, and restored with
+/// [`FileDialog::from_state`].
+pub struct FileDialogState {
+  /// The current directory.
+  pub path: PathBuf,
+  /// What the files are sorted by.
+  pub sort_by: SortKey,
+  /// Sort in descending order.
+  pub sort_reverse: bool,
+  /// Show hidden files.
+  pub show_hidden: bool,
+  /// How the files are laid out.
+  pub view_mode: ViewMode,
+  /// Size of the window content.
+  pub window_size: [f32; 2],
+}
+
+#[derive(Clone, Copy, Debug)]
+/// Text of the buttons and labels of the dialog, e.g. for translations. The default is English.
+pub struct FileDialogLabels {
+  /// Title of folder selection dialogs.
+  pub select_folder_title: &'static str,
+  /// Title of dialogs that open files.
+  pub open_file_title: &'static str,
+  /// Title of dialogs that save files.
+  pub save_file_title: &'static str,
+  /// Open button.
+  pub open_button: &'static str,
+  /// Save button.
+  pub save_button: &'static str,
+  /// Cancel button.
+  pub cancel_button: &'static str,
+  /// New Folder button.
+  pub new_folder_button: &'static str,
+  /// Suggested name of new folders.
+  pub new_folder_name: &'static str,
+  /// Button that creates the new folder.
+  pub create_button: &'static str,
+  /// Rename button.
+  pub rename_button: &'static str,
+  /// Delete button.
+  pub delete_button: &'static str,
+  /// Hover text of the refresh button.
+  pub refresh_button_hover: &'static str,
+  /// Menu of the recently visited folders, when icons are turned off.
+  pub recent_button: &'static str,
+  /// List view button, when icons are turned off.
+  pub list_view_button: &'static str,
+  /// Details view button, when icons are turned off.
+  pub details_view_button: &'static str,
+  /// Grid view button, when icons are turned off.
+  pub grid_view_button: &'static str,
+  /// Hover text of the parent folder button.
+  pub parent_folder_button_hover: &'static str,
+  /// Label of the file name field.
+  pub file_label: &'static str,
+  /// Show Hidden checkbox.
+  pub show_hidden_checkbox: &'static str,
+  /// Select All button in multi-select mode.
+  pub select_all_button: &'static str,
+  /// Button that clears the multi selection.
+  pub clear_selection_button: &'static str,
+  /// Context menu entry that copies the path.
+  pub copy_path: &'static str,
+  /// Context menu entry and hover text that show the entry in the file manager.
+  pub show_in_file_manager: &'static str,
+  /// Context menu entry that opens the file with the default app.
+  pub open_with_default_app: &'static str,
+  /// Heading of the bookmarks in the sidebar.
+  pub bookmarks: &'static str,
+  /// Shown while a folder is being read.
+  pub loading: &'static str,
+  /// Shown instead of the entries of a folder that can't be read for lack of permissions.
+  pub permission_denied: &'static str,
+  /// Button that returns from a folder that can't be read.
+  pub go_back_button: &'static str,
+  /// Button that confirms a question.
+  pub yes_button: &'static str,
+  /// Button that declines a question.
+  pub no_button: &'static str,
+  /// Header of the name column.
+  pub name_header: &'static str,
+  /// Header of the size column.
+  pub size_header: &'static str,
+  /// Header of the modification time column.
+  pub modified_header: &'static str,
+  /// Header of the permissions column.
+  pub permissions_header: &'static str,
+  /// Title of the question before saving over a file.
+  pub overwrite_title: &'static str,
+  /// Question before saving over the file `{name}`.
+  pub overwrite_text: &'static str,
+  /// Hover text of the button that bookmarks the current folder.
+  pub bookmark_hover: &'static str,
+  /// Shown instead of the preview of a binary file.
+  pub binary_file: &'static str,
+  /// Shown if there is no preview of the selected entry.
+  pub no_preview: &'static str,
+  /// Shown instead of the preview of a file that can't be read, with the `{error}`.
+  pub preview_error: &'static str,
+  /// Button that confirms the summary of a multi selection.
+  pub proceed_button: &'static str,
+  /// Button that returns from the summary of a multi selection.
+  pub back_button: &'static str,
+  /// Notification after creating a folder.
+  pub folder_created: &'static str,
+  /// Notification after renaming an entry.
+  pub renamed: &'static str,
+  /// Error `{error}` while creating a folder.
+  pub create_folder_error: &'static str,
+  /// Error `{error}` while renaming an entry.
+  pub rename_error: &'static str,
+  /// Error `{error}` while opening `{path}`.
+  pub open_error: &'static str,
+  /// Error `{error}` while showing `{path}` in the file manager.
+  pub show_error: &'static str,
+  /// Quick access shortcut to the home folder.
+  pub home: &'static str,
+  /// Quick access shortcut to the desktop folder.
+  pub desktop: &'static str,
+  /// Quick access shortcut to the documents folder.
+  pub documents: &'static str,
+  /// Quick access shortcut to the downloads folder.
+  pub downloads: &'static str,
+  /// Status bar with a single entry.
+  pub one_item: &'static str,
+  /// Status bar with `{count}` entries.
+  pub items: &'static str,
+  /// Size `{count}` of the multi selection.
+  pub selected_count: &'static str,
+  /// Size `{count}` of the multi selection, with a maximum of `{max}`.
+  pub selected_max: &'static str,
+  /// Size `{count}` of the multi selection, with a minimum of `{min}` and a maximum of `{max}`.
+  pub selected_range: &'static str,
+  /// Size `{count}` of the multi selection, with a minimum of `{min}`.
+  pub selected_min: &'static str,
+  /// Summary of the `{count}` selected entries with a total of `{size}`.
+  pub selected_size: &'static str,
+  /// Modification time less than a minute ago.
+  pub just_now: &'static str,
+  /// Modification time a minute ago.
+  pub minute_ago: &'static str,
+  /// Modification time `{count}` minutes ago.
+  pub minutes_ago: &'static str,
+  /// Modification time an hour ago.
+  pub hour_ago: &'static str,
+  /// Modification time `{count}` hours ago.
+  pub hours_ago: &'static str,
+  /// Modification time a day ago.
+  pub day_ago: &'static str,
+  /// Modification time `{count}` days ago.
+  pub days_ago: &'static str,
+  /// Modification time a week ago.
+  pub week_ago: &'static str,
+  /// Modification time `{count}` weeks ago.
+  pub weeks_ago: &'static str,
+  /// Hover text with the `{target}` of a symbolic link.
+  pub link_target: &'static str,
+  /// Hover text with the `{target}` of a symbolic link that doesn't exist.
+  pub broken_link_target: &'static str,
+  /// Warning that whitespace around the typed file name is removed.
+  pub trimmed_warning: &'static str,
+  /// Warning that Windows removes dots at the end of the typed file name.
+  pub trailing_dot_warning: &'static str,
+  /// Suggested name of a copy of the file named `{name}`.
+  pub copy_name: &'static str,
+  /// Suggested name of copy `{number}` of the file named `{name}`.
+  pub numbered_copy_name: &'static str,
+  /// Title of the question before cancelling with a typed file name.
+  pub discard_title: &'static str,
+  /// Question before cancelling with the typed file name `{name}`.
+  pub discard_text: &'static str,
+  /// Title of the questions before deleting.
+  pub delete_title: &'static str,
+  /// Question before deleting the entry `{name}`.
+  pub delete_text: &'static str,
+  /// Question before moving the entry `{name}` to the trash.
+  pub trash_text: &'static str,
+  /// Question before deleting the folder `{name}` with `{count}` items in it.
+  pub delete_folder_text: &'static str,
+  /// Question before moving the folder `{name}` with `{count}` items in it to the trash.
+  pub trash_folder_text: &'static str,
+  /// Question before deleting the `{count}` entries of the multi selection.
+  pub delete_selection_text: &'static str,
+  /// Question before moving the `{count}` entries of the multi selection to the trash.
+  pub trash_selection_text: &'static str,
+  /// Notification after deleting an entry.
+  pub deleted: &'static str,
+  /// Notification after moving an entry to the trash.
+  pub trashed: &'static str,
+  /// Notification after deleting `{count}` entries.
+  pub deleted_items: &'static str,
+  /// Notification after moving `{count}` entries to the trash.
+  pub trashed_items: &'static str,
+  /// Error `{error}` while deleting.
+  pub delete_error: &'static str,
+  /// Error `{error}` why files can't be saved in the current folder.
+  pub write_error: &'static str,
+  /// Error that the file `{name}` can't be saved over as it is read-only.
+  pub read_only_error: &'static str,
+  /// Error that the name of a new or renamed entry is empty.
+  pub empty_name_error: &'static str,
+  /// Error that the name of a new or renamed entry is "." or "..".
+  pub reserved_name_error: &'static str,
+  /// Error that the name of a new or renamed entry contains characters that aren't allowed.
+  pub illegal_name_error: &'static str,
+  /// Error that the name of a new or renamed entry ends with a dot or a space on Windows.
+  pub trailing_name_error: &'static str,
+  /// Error that an entry with the name of a new or renamed entry exists.
+  pub existing_name_error: &'static str,
+}
+
+impl Default for FileDialogLabels {
+  fn default() -> Self {
+    Self {
+      select_folder_title: "Select Folder",
+      open_file_title: "Open File",
+      save_file_title: "Save File",
+      open_button: "Open",
+      save_button: "Save",
+      cancel_button: "Cancel",
+      new_folder_button: "New Folder",
+      new_folder_name: "New folder",
+      create_button: "Create",
+      rename_button: "Rename",
+      delete_button: "Delete",
+      refresh_button_hover: "Refresh",
+      recent_button: "Recent",
+      list_view_button: "List",
+      details_view_button: "Details",
+      grid_view_button: "Grid",
+      parent_folder_button_hover: "Parent Folder",
+      file_label: "File:",
+      show_hidden_checkbox: "Show Hidden",
+      select_all_button: "Select All",
+      clear_selection_button: "Clear",
+      copy_path: "Copy Path",
+      show_in_file_manager: "Show in File Manager",
+      open_with_default_app: "Open with Default App",
+      bookmarks: "Bookmarks",
+      loading: "Loading…",
+      permission_denied: "Permission denied",
+      go_back_button: "Go back",
+      yes_button: "Yes",
+      no_button: "No",
+      name_header: "Name",
+      size_header: "Size",
+      modified_header: "Modified",
+      permissions_header: "Permissions",
+      overwrite_title: "Overwrite?",
+      overwrite_text: "\"{name}\" already exists. Overwrite existing file?",
+      bookmark_hover: "Bookmark this folder",
+      binary_file: "Binary file",
+      no_preview: "No preview",
+      preview_error: "Can't read the file: {error}",
+      proceed_button: "Proceed",
+      back_button: "Back",
+      folder_created: "Folder created",
+      renamed: "Renamed",
+      create_folder_error: "Error while creating directory: {error}",
+      rename_error: "Error while renaming: {error}",
+      open_error: "Can't open {path}: {error}",
+      show_error: "Can't show {path}: {error}",
+      home: "Home",
+      desktop: "Desktop",
+      documents: "Documents",
+      downloads: "Downloads",
+      one_item: "1 item",
+      items: "{count} items",
+      selected_count: "{count} selected",
+      selected_max: "{count} selected (max {max})",
+      selected_range: "{count} selected ({min} to {max})",
+      selected_min: "{count} selected (min {min})",
+      selected_size: "{count} selected ({size})",
+      just_now: "just now",
+      minute_ago: "1 minute ago",
+      minutes_ago: "{count} minutes ago",
+      hour_ago: "1 hour ago",
+      hours_ago: "{count} hours ago",
+      day_ago: "1 day ago",
+      days_ago: "{count} days ago",
+      week_ago: "1 week ago",
+      weeks_ago: "{count} weeks ago",
+      link_target: "→ {target}",
+      broken_link_target: "→ {target} (broken link)",
+      trimmed_warning: "Leading and trailing spaces will be removed",
+      trailing_dot_warning: "Trailing dots will be removed by Windows",
+      copy_name: "{name} copy",
+      numbered_copy_name: "{name} copy {number}",
+      discard_title: "Discard?",
+      discard_text: "Close without saving \"{name}\"?",
+      delete_title: "Delete?",
+      delete_text: "Delete \"{name}\"? This can't be undone.",
+      trash_text: "Move \"{name}\" to the trash?",
+      delete_folder_text: "Delete folder \"{name}\" and {count} items? This can't be undone.",
+      trash_folder_text: "Move folder \"{name}\" and {count} items to the trash?",
+      delete_selection_text: "Delete {count} items? This can't be undone.",
+      trash_selection_text: "Move {count} items to the trash?",
+      deleted: "Deleted",
+      trashed: "Moved to the trash",
+      deleted_items: "Deleted {count} items",
+      trashed_items: "Moved {count} items to the trash",
+      delete_error: "Error while deleting: {error}",
+      write_error: "Can't save in this folder: {error}",
+      read_only_error: "\"{name}\" is read-only",
+      empty_name_error: "Enter a name",
+      reserved_name_error: "This name is reserved",
+      illegal_name_error: "The name contains characters that aren't allowed",
+      trailing_name_error: "The name can't end with a dot or a space",
+      existing_name_error: "A file or folder with this name already exists",
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug)]
+/// Emoji shown for entries, on buttons and before the title. They can be replaced with glyphs of
+/// another font, or turned off with [`FileDialog::show_icons`].
+pub struct Icons {
+  /// Folders.
+  pub folder: &'static str,
+  /// Files.
+  pub file: &'static str,
+  /// Executable files on unix.
+  pub executable: &'static str,
+  /// Symbolic links.
+  pub symlink: &'static str,
+  /// Symbolic links whose target doesn't exist.
+  pub broken_link: &'static str,
+  /// After the name of read-only entries.
+  pub read_only: &'static str,
+  /// Button that goes up a folder.
+  pub parent_folder: &'static str,
+  /// Menu of the recently visited folders.
+  pub recent: &'static str,
+  /// Refresh button.
+  pub refresh: &'static str,
+  /// Button that shows the folder in the file manager.
+  pub file_manager: &'static str,
+  /// List view button.
+  pub list_view: &'static str,
+  /// Details view button.
+  pub details_view: &'static str,
+  /// Grid view button.
+  pub grid_view: &'static str,
+  /// Before the title of folder selection dialogs.
+  pub select_folder: &'static str,
+  /// Before the title of dialogs that open files.
+  pub open_file: &'static str,
+  /// Before the title of dialogs that save files.
+  pub save_file: &'static str,
+  /// Mounted drives in the sidebar on unix.
+  pub mount: &'static str,
+}
+
+impl Default for Icons {
+  fn default() -> Self {
+    Self {
+      folder: "🗀",
+      file: "🗋",
+      executable: "⚙",
+      symlink: "🔗",
+      broken_link: "⚠",
+      read_only: "🔒",
+      parent_folder: "⬆",
+      recent: "🕘",
+      refresh: "⟲",
+      file_manager: "🗁",
+      list_view: "☰",
+      details_view: "📋",
+      grid_view: "🖼",
+      select_folder: "📁",
+      open_file: "📂",
+      save_file: "💾",
+      mount: "🖴",
+    }
+  }
+}
+
+impl Icons {
+  /// Icon of an entry in the list and grid views.
+  fn entry(&self, info: &FileInfo) -> &'static str {
+    if info.is_broken_link() {
+      self.broken_link
+    } else if info.is_symlink() {
+      self.symlink
+    } else if info.is_dir() {
+      self.folder
+    } else if is_executable(info) {
+      self.executable
+    } else {
+      self.file
+    }
+  }
+
+  fn view_mode(&self, view_mode: ViewMode) -> &'static str {
+    match view_mode {
+      ViewMode::List => self.list_view,
+      ViewMode::Details => self.details_view,
+      ViewMode::Grid { .. } => self.grid_view,
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+/// Filesystem operation performed by the dialog.
+pub enum FsOp {
+  CreateDirectory,
+  Rename,
+  Delete,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+/// How the Delete button removes files.
+pub enum DeleteMode {
+  /// Move to the trash or recycle bin. Without the `trash` feature this deletes permanently.
+  #[cfg_attr(feature = "trash", default)]
+  Trash,
+  /// Delete permanently.
+  #[cfg_attr(not(feature = "trash"), default)]
+  Permanent,
+}
+
+#[derive(Debug)]
+/// Result of a filesystem operation performed by the dialog.
+pub enum FsEvent {
+  /// A folder was created.
+  Created(PathBuf),
+  /// A file or folder was renamed.
+  Renamed { from: PathBuf, to: PathBuf },
+  /// A file or folder was deleted.
+  Deleted(PathBuf),
+  /// An operation failed.
+  Error { op: FsOp, error: Error },
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+/// Severity of a message shown with [`FileDialog::notify`].
+pub enum Level {
+  Info,
+  Warning,
+  Error,
+}
+
+#[derive(Debug)]
+/// Entries of a directory as they were read, kept to navigate back without reading it again.
+struct CachedListing {
+  path: PathBuf,
+  read_time: SystemTime,
+  entries: Vec<FileInfo>,
+}
+
+#[cfg(feature = "watch")]
+#[derive(Debug)]
+/// Watcher of the current directory.
+struct Watch {
+  path: PathBuf,
+
+  /// Not set if the directory can't be watched.
+  _watcher: Option<notify::RecommendedWatcher>,
+  receiver: mpsc::Receiver<notify::Result<notify::Event>>,
+
+  /// Time of the last change that hasn't been refreshed yet.
+  changed_at: Option<f64>,
+}
+
+#[derive(Debug)]
+/// Entry that is being renamed in its row of the file list.
+struct InlineRename {
+  path: PathBuf,
+  name: String,
+
+  /// Set until the text field got the focus.
+  focus: bool,
+}
+
+#[derive(Debug)]
+struct Toast {
+  text: String,
+  level: Level,
+
+  /// Time when the toast was first shown.
+  shown_at: Option<f64>,
+}
+
 /// `egui` component that represents `OpenFileDialog` or `SaveFileDialog`.
 pub struct FileDialog {
+  /// Path the dialog was created with, split again if the file system is replaced.
+  initial_path: PathBuf,
+
   /// Current opened path.
   path: PathBuf,
 
   /// Editable field with path.
   path_edit: String,
 
+  /// Folders that complete the text of the path field.
+  path_suggestions: Vec<PathBuf>,
+
+  /// Separator used when displaying paths.
+  display_separator: char,
+
   /// Selected file path (single select mode).
   selected_file: Option<FileInfo>,
 
   /// Editable field with filename.
   filename_edit: String,
 
-  /// Dialog title text
-  title: String,
+  /// Give the file name field the keyboard focus in the next frame.
+  focus_filename: bool,
 
-  /// Open button text
-  open_button_text: &'static str,
+  /// Give the file list the keyboard focus in the next frame.
+  focus_list: bool,
 
-  /// Save button text
-  save_button_text: &'static str,
+  /// Dialog title text
+  title: String,
 
-  /// Cancel button text
-  cancel_button_text: &'static str,
+  /// Show an emoji for the dialog type before the title.
+  title_icon: bool,
 
-  /// New Folder button text
-  new_folder_button_text: &'static str,
+  /// Text of the buttons and labels.
+  labels: FileDialogLabels,
 
-  /// New Folder name text
-  new_folder_name_text: &'static str,
+  /// Shown in place of the entries of an empty folder.
+  empty_text: String,
 
-  /// Rename button text
-  rename_button_text: &'static str,
+  icons: Icons,
 
-  /// Refresh button hover text
-  refresh_button_hover_text: &'static str,
+  /// Show icons, otherwise buttons show text instead.
+  show_icons: bool,
 
-  /// Parent Folder button hover text
-  parent_folder_button_hover_text: &'static str,
+  /// Files in directory.
+  files: Result<Vec<FileInfo>, Error>,
 
-  /// File label text
-  file_label_text: &'static str,
+  /// Receives the entries of the current path while they are read on a background thread.
+  pending_listing: Option<mpsc::Receiver<Result<Vec<FileInfo>, Error>>>,
 
-  /// Show Hidden checkbox text
-  show_hidden_checkbox_text: &'static str,
+  /// Set while the entries of the current path are being read.
+  loading: bool,
 
-  /// Files in directory.
-  files: Result<Vec<FileInfo>, Error>,
+  /// Paths that are selected again once the entries have been read.
+  keep_selection: Vec<PathBuf>,
 
   /// Current dialog state.
   state: State,
 
+  /// Set when the selection is confirmed during `show`, so the state changes in the same frame.
+  confirmed: bool,
+
+  /// Set once the confirmed selection was returned by `take_confirmed`.
+  consumed: bool,
+
+  /// Ask before saving over an existing file.
+  confirm_overwrite: bool,
+
+  /// Save under a numbered name instead of over an existing file.
+  unique_on_conflict: bool,
+
+  /// Ask before cancelling a save dialog with a typed file name.
+  confirm_cancel: bool,
+
+  /// Question the user has to answer before the dialog continues.
+  prompt: Option<Prompt>,
+
   /// Dialog type.
   dialog_type: DialogType,
 
+  /// Error shown in the dialog until it is dismissed.
+  last_error: Option<String>,
+
+  /// Messages that disappear after a few seconds.
+  toasts: Vec<Toast>,
+
+  /// Most recently visited directories, the current one first.
+  recent: VecDeque<PathBuf>,
+
+  /// Maximum number of recent directories.
+  recent_limit: usize,
+
+  /// Listings of recently read directories, the most recently used first.
+  cache: VecDeque<CachedListing>,
+
+  /// Maximum number of cached listings.
+  cache_limit: usize,
+
+  /// Show the sidebar with bookmarks.
+  show_sidebar: bool,
+
+  /// Show the folder tree.
+  show_tree: bool,
+
+  /// Subfolders of the folders that were expanded in the tree.
+  tree: HashMap<PathBuf, Vec<PathBuf>>,
+
+  /// The current path when the tree was last shown, to expand it when it changes.
+  tree_path: Option<PathBuf>,
+
+  /// Directories pinned by the user.
+  bookmarks: Vec<PathBuf>,
+
+  /// Well-known directories (Home, Documents, etc.) shown in the sidebar.
+  quick_access: Vec<(String, PathBuf)>,
+
+  /// How the path is shown.
+  path_bar_mode: PathBarMode,
+
+  /// Whether the breadcrumb bar currently shows the text field.
+  editing_path: bool,
+
+  /// How the files are laid out.
+  view_mode: ViewMode,
+
+  /// Number of columns when switching to the grid view.
+  grid_columns: usize,
+
+  /// Size of thumbnails in the grid view.
+  thumbnail_size: Vec2,
+
+  /// Decoded thumbnails of the current folder, `None` if the image couldn't be decoded.
+  #[cfg(feature = "image")]
+  thumbnails: HashMap<PathBuf, Option<egui::TextureHandle>>,
+
+  /// Show a preview of the selected text file.
+  show_text_preview: bool,
+
+  /// Maximum number of bytes read for the text preview.
+  preview_max_bytes: usize,
+
+  /// Path of the previewed file and its text, `None` if it isn't a text file or the error
+  /// reading it.
+  text_preview: Option<(PathBuf, Result<Option<String>, Error>)>,
+
+  /// Show a preview of the selected image.
+  #[cfg(feature = "image")]
+  show_preview: bool,
+
+  /// Path of the previewed file and its image, `None` if it couldn't be decoded.
+  #[cfg(feature = "image")]
+  preview: Option<(PathBuf, Option<egui::TextureHandle>)>,
+
+  /// What the files are sorted by.
+  sort_by: SortKey,
+
+  /// Sort in descending order.
+  sort_reverse: bool,
+
   id: Option<Id>,
   current_pos: Option<Pos2>,
   default_pos: Option<Pos2>,
   default_size: Vec2,
+
+  /// Extension appended to file names without one when saving.
+  default_extension: Option<String>,
+
+  /// Opened instead of the initial path if that no longer exists.
+  fallback_path: Option<PathBuf>,
+
+  /// Size of the window content when it was last shown.
+  window_size: Vec2,
+
   anchor: Option<(Align2, Vec2)>,
   show_files_filter: Filter<PathBuf>,
+  show_dirs_filter: Filter<PathBuf>,
+
+  /// Named filters the user can choose from, e.g. "Images", with the extension that saved files
+  /// get while the filter is chosen.
+  filters: Vec<(String, Filter<PathBuf>, Option<String>)>,
+
+  /// Index of the chosen named filter.
+  active_filter: usize,
+
   filename_filter: Filter<String>,
+  fs_event_handler: Option<FsEventHandler>,
+  row_renderer: Option<RowRenderer>,
+  file_system: Arc<dyn FileSystem>,
   range_start: Option<usize>,
+
+  /// Entry moved to with the keyboard, which Enter acts upon.
+  focused_index: Option<usize>,
+
+  /// Number of rows that fit into the file list, for PageUp and PageDown.
+  page_rows: usize,
+
+  /// Characters typed while the list has focus, and when the last one was typed.
+  type_ahead: String,
+  type_ahead_time: f64,
+
+  initial_highlight: Option<usize>,
+  scroll_to_row: Option<usize>,
+
+  /// Text of the search field.
+  search_query: String,
+
+  /// Index of the entry that was last found by the search.
+  search_match: Option<usize>,
+
+  /// Indices of the entries that match the search query.
+  visible: Vec<usize>,
+
   resizable: bool,
   rename: bool,
+
+  /// Entry being renamed in the file list.
+  inline_rename: Option<InlineRename>,
+
+  delete: bool,
+  delete_mode: DeleteMode,
+
+  /// Delete folders with everything in them.
+  delete_recursive: bool,
+
   new_folder: bool,
   multi_select_enabled: bool,
+
+  /// Minimum and maximum number of entries in a multi selection.
+  selection_limits: (usize, Option<usize>),
+
   keep_on_top: bool,
+
+  /// Dim the rest of the UI and block its input while the dialog is open.
+  modal: bool,
   show_system_files: bool,
 
+  /// Show files whose name starts with a dot, and on Windows files with the hidden attribute.
+  show_hidden: bool,
+
+  /// Shortcut that toggles `show_hidden`.
+  show_hidden_shortcut: Option<egui::KeyboardShortcut>,
+
+  check_readable: bool,
+
+  /// Folder to go back to if the background read of the current one fails, for
+  /// `check_readable` with `async_loading`.
+  back_on_error: Option<PathBuf>,
+
+  /// Check that the current folder is writable before saving.
+  check_writable: bool,
+
+  /// Folder that was last checked for writability, with the reason if it isn't.
+  write_error: Option<(PathBuf, Option<String>)>,
+
+  follow_symlinks: bool,
+
+  /// Targets of the selected symbolic links, resolved when the selection was confirmed.
+  link_targets: HashMap<PathBuf, PathBuf>,
+
+  lazy_metadata: bool,
+  async_loading: bool,
+  virtualize: bool,
+  show_parent_entry: bool,
+  trim_filename: bool,
+  show_size: bool,
+  show_modified: bool,
+
+  /// Strftime-style format of the modification time.
+  date_format: String,
+
+  /// The date format, parsed once. Empty if the format is invalid.
+  date_items: Vec<chrono::format::Item<'static>>,
+  date_display: DateDisplay,
+  confirm_multi_summary: bool,
+  show_multi_summary: bool,
+
   /// Show drive letters on Windows.
   #[cfg(windows)]
   show_drives: bool,
 
-  /// Show hidden files on unix systems.
+  /// Known folders (AppData, ProgramData, etc.) shown in the sidebar on Windows.
+  #[cfg(windows)]
+  known_folders: Vec<(&'static str, PathBuf)>,
+
+  /// Show mounted volumes in the sidebar on unix systems.
   #[cfg(unix)]
-  show_hidden: bool,
+  show_mounts: bool,
+
+  /// Show the permissions column in the details view on unix systems.
+  #[cfg(unix)]
+  show_permissions: bool,
+
+  /// Mounted volumes, read again on refresh.
+  #[cfg(unix)]
+  mounts: Vec<PathBuf>,
+
+  /// Refresh the listing when the current directory changes on disk.
+  #[cfg(feature = "watch")]
+  watch: bool,
+
+  #[cfg(feature = "watch")]
+  watcher: Option<Watch>,
 }
 
 impl Debug for FileDialog {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     let mut dbg = f.debug_struct("FileDialog");
     let dbg = dbg
+      .field("initial_path", &self.initial_path)
       .field("path", &self.path)
       .field("path_edit", &self.path_edit)
+      .field("path_suggestions", &self.path_suggestions)
+      .field("empty_text", &self.empty_text)
+      .field("display_separator", &self.display_separator)
       .field("selected_file", &self.selected_file)
       .field("filename_edit", &self.filename_edit)
+      .field("focus_filename", &self.focus_filename)
+      .field("focus_list", &self.focus_list)
       .field("files", &self.files)
+      .field("pending_listing", &self.pending_listing)
+      .field("loading", &self.loading)
+      .field("keep_selection", &self.keep_selection)
       .field("state", &self.state)
+      .field("confirmed", &self.confirmed)
+      .field("consumed", &self.consumed)
+      .field("confirm_overwrite", &self.confirm_overwrite)
+      .field("unique_on_conflict", &self.unique_on_conflict)
+      .field("confirm_cancel", &self.confirm_cancel)
+      .field("prompt", &self.prompt)
       .field("dialog_type", &self.dialog_type)
+      .field("recent", &self.recent)
+      .field("recent_limit", &self.recent_limit)
+      .field("cache", &self.cache)
+      .field("cache_limit", &self.cache_limit)
+      .field("show_sidebar", &self.show_sidebar)
+      .field("show_tree", &self.show_tree)
+      .field("tree", &self.tree)
+      .field("tree_path", &self.tree_path)
+      .field("bookmarks", &self.bookmarks)
+      .field("quick_access", &self.quick_access)
+      .field("path_bar_mode", &self.path_bar_mode)
+      .field("editing_path", &self.editing_path)
+      .field("view_mode", &self.view_mode)
+      .field("grid_columns", &self.grid_columns)
+      .field("thumbnail_size", &self.thumbnail_size)
+      .field("show_text_preview", &self.show_text_preview)
+      .field("preview_max_bytes", &self.preview_max_bytes)
+      .field("sort_by", &self.sort_by)
+      .field("sort_reverse", &self.sort_reverse)
       .field("current_pos", &self.current_pos)
       .field("default_pos", &self.default_pos)
       .field("default_size", &self.default_size)
+      .field("window_size", &self.window_size)
+      .field("default_extension", &self.default_extension)
+      .field("active_filter", &self.active_filter_name())
+      .field("fallback_path", &self.fallback_path)
       .field("anchor", &self.anchor)
       .field("resizable", &self.resizable)
       .field("rename", &self.rename)
+      .field("inline_rename", &self.inline_rename)
+      .field("delete", &self.delete)
+      .field("delete_mode", &self.delete_mode)
+      .field("delete_recursive", &self.delete_recursive)
       .field("new_folder", &self.new_folder)
       .field("multi_select", &self.multi_select_enabled)
+      .field("selection_limits", &self.selection_limits)
       .field("range_start", &self.range_start)
+      .field("focused_index", &self.focused_index)
+      .field("page_rows", &self.page_rows)
+      .field("type_ahead", &self.type_ahead)
+      .field("initial_highlight", &self.initial_highlight)
+      .field("scroll_to_row", &self.scroll_to_row)
+      .field("search_query", &self.search_query)
+      .field("search_match", &self.search_match)
+      .field("visible", &self.visible)
       .field("keep_on_top", &self.keep_on_top)
-      .field("show_system_files", &self.show_system_files);
+      .field("modal", &self.modal)
+      .field("show_system_files", &self.show_system_files)
+      .field("show_hidden", &self.show_hidden)
+      .field("show_hidden_shortcut", &self.show_hidden_shortcut)
+      .field("check_readable", &self.check_readable)
+      .field("back_on_error", &self.back_on_error)
+      .field("check_writable", &self.check_writable)
+      .field("write_error", &self.write_error)
+      .field("follow_symlinks", &self.follow_symlinks)
+      .field("link_targets", &self.link_targets)
+      .field("lazy_metadata", &self.lazy_metadata)
+      .field("async_loading", &self.async_loading)
+      .field("virtualize", &self.virtualize)
+      .field("show_parent_entry", &self.show_parent_entry)
+      .field("trim_filename", &self.trim_filename)
+      .field("show_size", &self.show_size)
+      .field("show_modified", &self.show_modified)
+      .field("date_format", &self.date_format)
+      .field("date_items", &self.date_items)
+      .field("date_display", &self.date_display)
+      .field("confirm_multi_summary", &self.confirm_multi_summary)
+      .field("show_multi_summary", &self.show_multi_summary)
+      .field("last_error", &self.last_error)
+      .field("toasts", &self.toasts);
 
     // Closures don't implement std::fmt::Debug.
     // let dbg = dbg
     //   .field("shown_files_filter", &self.shown_files_filter)
-    //   .field("filename_filter", &self.filename_filter);
+    //   .field("show_dirs_filter", &self.show_dirs_filter)
+    //   .field("filename_filter", &self.filename_filter)
+    //   .field("row_renderer", &self.row_renderer);
 
     #[cfg(unix)]
-    let dbg = dbg.field("show_hidden", &self.show_hidden);
+    let dbg = dbg
+      .field("show_mounts", &self.show_mounts)
+      .field("show_permissions", &self.show_permissions)
+      .field("mounts", &self.mounts);
+
+    #[cfg(feature = "image")]
+    let dbg = dbg.field("show_preview", &self.show_preview);
+
+    #[cfg(feature = "watch")]
+    let dbg = dbg
+      .field("watch", &self.watch)
+      .field("watcher", &self.watcher);
 
     #[cfg(windows)]
-    let dbg = dbg.field("show_drives", &self.show_drives);
+    let dbg = dbg
+      .field("show_drives", &self.show_drives)
+      .field("known_folders", &self.known_folders);
 
     dbg.finish()
   }
 }
 
-/// Function that returns `true` if the path is accepted.
-pub type Filter<T> = Box<dyn Fn(&<T as Deref>::Target) -> bool + Send + Sync + 'static>;
+/// Source of the entries listed by the dialog and target of its operations. Implement this to
+/// list virtual folders or to test code using the dialog without touching the disk. Previews
+/// and thumbnails are always read from the disk.
+pub trait FileSystem: Send + Sync {
+  /// Entries of the folder at `path`. With `lazy_metadata`, the metadata should only be read
+  /// once it is needed.
+  fn read_dir(&self, path: &Path, lazy_metadata: bool) -> Result<Vec<FileInfo>, Error>;
 
-impl FileDialog {
-  /// Create dialog that prompts the user to select a folder.
-  pub fn select_folder(initial_path: Option<PathBuf>) -> Self {
-    FileDialog::new(DialogType::SelectFolder, initial_path)
-  }
+  /// The entry at `path`. Entries that don't exist are neither files nor folders.
+  fn entry(&self, path: &Path) -> FileInfo;
 
-  /// Create dialog that prompts the user to open a file.
-  pub fn open_file(initial_path: Option<PathBuf>) -> Self {
-    FileDialog::new(DialogType::OpenFile, initial_path)
-  }
+  /// Create a folder.
+  fn create_dir(&self, path: &Path) -> Result<(), Error>;
 
-  /// Create dialog that prompts the user to save a file.
-  pub fn save_file(initial_path: Option<PathBuf>) -> Self {
-    FileDialog::new(DialogType::SaveFile, initial_path)
-  }
+  /// Rename or move a file or folder.
+  fn rename(&self, from: &Path, to: &Path) -> Result<(), Error>;
 
-  /// Constructs new file dialog. If no `initial_path` is passed,`env::current_dir` is used.
-  fn new(dialog_type: DialogType, initial_path: Option<PathBuf>) -> Self {
-    let mut path = initial_path.unwrap_or_else(|| env::current_dir().unwrap_or_default());
-    let mut filename_edit = String::new();
-    let info = FileInfo::new(path.clone());
+  /// Delete a file or an empty folder.
+  fn remove(&self, path: &Path) -> Result<(), Error>;
 
-    if info.is_file() {
-      assert!(dialog_type != DialogType::SelectFolder);
-      filename_edit = get_file_name(&info).to_string();
-      path.pop();
+  /// Delete a file or a folder with everything in it. Links are removed, not what they point to.
+  fn remove_all(&self, path: &Path) -> Result<(), Error> {
+    let entry = self.entry(path);
+    if entry.is_dir() && !entry.is_symlink() {
+      for child in self.read_dir(path, true)? {
+        self.remove_all(&child.path)?;
+      }
     }
+    self.remove(path)
+  }
 
-    let path_edit = path.to_str().unwrap_or_default().to_string();
-    Self {
-      path,
-      path_edit,
-      selected_file: None,
-      filename_edit,
-      title: match dialog_type {
-        DialogType::SelectFolder => "📁  Select Folder",
-        DialogType::OpenFile => "📂  Open File",
-        DialogType::SaveFile => "💾  Save File",
-      }
-      .to_string(),
-      open_button_text: "Open",
-      save_button_text: "Save",
-      cancel_button_text: "Cancel",
-      new_folder_button_text: "New Folder",
-      new_folder_name_text: "New folder",
-      rename_button_text: "Rename",
-      refresh_button_hover_text: "Refresh",
-      parent_folder_button_hover_text: "Parent Folder",
-      file_label_text: "File:",
-      show_hidden_checkbox_text: "Show Hidden",
-      files: Ok(Vec::new()),
-      state: State::Closed,
-      dialog_type,
+  /// Check that the folder at `path` can be listed.
+  fn check_readable(&self, path: &Path) -> Result<(), Error> {
+    self.read_dir(path, true).map(|_| ())
+  }
 
-      id: None,
-      current_pos: None,
-      default_pos: None,
-      default_size: egui::vec2(512.0, 512.0),
-      anchor: None,
-      show_files_filter: Box::new(|_| true),
-      filename_filter: Box::new(|_| true),
-      resizable: true,
-      rename: true,
-      new_folder: true,
+  /// Check that files can be created in the folder at `path`.
+  fn check_writable(&self, _path: &Path) -> Result<(), Error> {
+    Ok(())
+  }
 
-      #[cfg(windows)]
-      show_drives: true,
+  /// Returns true, if there is a file or folder at `path`.
+  fn exists(&self, path: &Path) -> bool {
+    let entry = self.entry(path);
+    entry.is_file() || entry.is_dir()
+  }
 
-      #[cfg(unix)]
-      show_hidden: false,
-      multi_select_enabled: false,
-      range_start: None,
-      keep_on_top: false,
-      show_system_files: false,
-    }
+  /// Returns true, if there is a folder at `path`.
+  fn is_dir(&self, path: &Path) -> bool {
+    self.entry(path).is_dir()
   }
 
-  /// Set the default file name.
-  pub fn default_filename(mut self, filename: impl Into<String>) -> Self {
-    self.filename_edit = filename.into();
-    self
+  /// The absolute path of `path` with symbolic links resolved. By default, `path` is returned as
+  /// it is.
+  fn canonicalize(&self, path: &Path) -> Result<PathBuf, Error> {
+    Ok(path.to_path_buf())
   }
 
-  /// Set the window title text.
-  pub fn title(mut self, title: &str) -> Self {
-    self.title = match self.dialog_type {
-      DialogType::SelectFolder => "📁  ",
-      DialogType::OpenFile => "📂  ",
-      DialogType::SaveFile => "💾  ",
-    }
-    .to_string()
-      + title;
-    self
+  /// Move a file or folder to the trash. By default, this isn't supported.
+  #[cfg(feature = "trash")]
+  fn trash(&self, _path: &Path) -> Result<(), Error> {
+    Err(Error::from(std::io::ErrorKind::Unsupported))
   }
+}
 
-  /// Set the open button text.
-  pub fn open_button_text(mut self, text: &'static str) -> Self {
-    self.open_button_text = text;
-    self
+#[derive(Clone, Copy, Debug, Default)]
+/// The file system of the operating system, used by default.
+pub struct StdFileSystem;
+
+impl FileSystem for StdFileSystem {
+  fn read_dir(&self, path: &Path, lazy_metadata: bool) -> Result<Vec<FileInfo>, Error> {
+    fs::read_dir(path).map(|entries| {
+      entries
+        .filter_map(|result| result.ok())
+        .map(|entry| FileInfo::from_entry(&entry, lazy_metadata))
+        .collect()
+    })
   }
 
-  /// Set the save button text.
-  pub fn save_button_text(mut self, text: &'static str) -> Self {
-    self.save_button_text = text;
-    self
+  fn entry(&self, path: &Path) -> FileInfo {
+    FileInfo::new(path.to_path_buf())
   }
 
-  /// Set the cancel button text.
-  pub fn cancel_button_text(mut self, text: &'static str) -> Self {
-    self.cancel_button_text = text;
-    self
+  fn create_dir(&self, path: &Path) -> Result<(), Error> {
+    fs::create_dir(path)
   }
 
-  /// Set the new folder button text.
-  pub fn new_folder_button_text(mut self, text: &'static str) -> Self {
-    self.new_folder_button_text = text;
-    self
+  fn rename(&self, from: &Path, to: &Path) -> Result<(), Error> {
+    fs::rename(from, to)
   }
 
-  /// Set the new folder name text.
-  pub fn new_folder_name_text(mut self, text: &'static str) -> Self {
-    self.new_folder_name_text = text;
-    self
+  fn remove(&self, path: &Path) -> Result<(), Error> {
+    match path.is_dir() {
+      true => fs::remove_dir(path),
+      false => fs::remove_file(path),
+    }
   }
 
-  /// Set the refresh button hover text.
-  pub fn refresh_button_hover_text(mut self, text: &'static str) -> Self {
-    self.refresh_button_hover_text = text;
-    self
+  fn remove_all(&self, path: &Path) -> Result<(), Error> {
+    match fs::symlink_metadata(path)?.is_dir() {
+      true => fs::remove_dir_all(path),
+      false => fs::remove_file(path),
+    }
   }
 
-  /// Set the parent folder button hover text.
-  pub fn parent_folder_button_hover_text(mut self, text: &'static str) -> Self {
-    self.parent_folder_button_hover_text = text;
-    self
+  fn canonicalize(&self, path: &Path) -> Result<PathBuf, Error> {
+    canonicalize(path)
   }
 
-  /// Set the rename button text.
-  pub fn rename_button_text(mut self, text: &'static str) -> Self {
-    self.rename_button_text = text;
-    self
+  #[cfg(feature = "trash")]
+  fn trash(&self, path: &Path) -> Result<(), Error> {
+    trash::delete(path).map_err(Error::other)
   }
 
-  /// Set the file label text.
-  pub fn file_label_text(mut self, text: &'static str) -> Self {
-    self.file_label_text = text;
-    self
+  fn check_readable(&self, path: &Path) -> Result<(), Error> {
+    fs::read_dir(path).map(|_| ())
   }
 
-  /// Set the show hidden checkbox text.
-  pub fn show_hidden_checkbox_text(mut self, text: &'static str) -> Self {
-    self.show_hidden_checkbox_text = text;
-    self
+  fn check_writable(&self, path: &Path) -> Result<(), Error> {
+    // Permissions alone don't tell, e.g. for read-only mounts and ACLs, so create a file.
+    let probe = path.join(format!(".egui_file_write_check_{}", std::process::id()));
+    match fs::OpenOptions::new()
+      .write(true)
+      .create_new(true)
+      .open(&probe)
+    {
+      Ok(_) => fs::remove_file(&probe),
+      Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => Ok(()),
+      Err(err) => Err(err),
+    }
   }
 
-  /// Set the window ID.
-  pub fn id(mut self, id: impl Into<Id>) -> Self {
-    self.id = Some(id.into());
-    self
+  fn exists(&self, path: &Path) -> bool {
+    path.exists()
   }
 
-  /// Set the window anchor.
-  pub fn anchor(mut self, align: Align2, offset: impl Into<Vec2>) -> Self {
-    self.anchor = Some((align, offset.into()));
-    self
+  fn is_dir(&self, path: &Path) -> bool {
+    path.is_dir()
   }
+}
 
-  /// Set the window's current position.
-  pub fn current_pos(mut self, current_pos: impl Into<Pos2>) -> Self {
-    self.current_pos = Some(current_pos.into());
-    self
-  }
+#[cfg(feature = "zip")]
+#[derive(Debug, Default)]
+/// The file system of the operating system, with ZIP archives listed as read-only folders. A
+/// file chosen inside an archive is returned as `<archive>.zip/<path in archive>`.
+pub struct ZipFileSystem {
+  /// Archives read so far, by their path.
+  archives: std::sync::Mutex<HashMap<PathBuf, Arc<ZipIndex>>>,
+}
 
-  /// Set the window's default position.
-  pub fn default_pos(mut self, default_pos: impl Into<Pos2>) -> Self {
-    self.default_pos = Some(default_pos.into());
-    self
+#[cfg(feature = "zip")]
+#[derive(Debug, Default)]
+/// Entries of a ZIP archive, read once from its central directory.
+struct ZipIndex {
+  /// Modification time of the archive when it was read.
+  modified: Option<SystemTime>,
+  /// Folders with the names of their children in the order of the archive, the root is the
+  /// empty path.
+  folders: HashMap<PathBuf, Vec<String>>,
+  /// Files with their size.
+  files: HashMap<PathBuf, u64>,
+}
+
+#[cfg(feature = "zip")]
+impl ZipIndex {
+  fn read(archive: &Path) -> Result<Self, Error> {
+    let file = fs::File::open(archive)?;
+    let modified = file.metadata().and_then(|meta| meta.modified()).ok();
+    let mut zip = zip::ZipArchive::new(file).map_err(Error::other)?;
+
+    let mut index = Self {
+      modified,
+      ..Self::default()
+    };
+    index.folders.insert(PathBuf::new(), Vec::new());
+    for idx in 0..zip.len() {
+      let entry = zip.by_index_raw(idx).map_err(Error::other)?;
+      let names: Vec<&str> = entry
+        .name()
+        .split('/')
+        .filter(|name| !name.is_empty())
+        .collect();
+
+      // Folders might only be implied by the paths of the files in them.
+      let mut parent = PathBuf::new();
+      for (depth, name) in names.iter().enumerate() {
+        let path = parent.join(name);
+        if !index.folders.contains_key(&path) && !index.files.contains_key(&path) {
+          index
+            .folders
+            .entry(parent)
+            .or_default()
+            .push(name.to_string());
+        }
+        if depth + 1 == names.len() && !entry.is_dir() {
+          index.files.insert(path.clone(), entry.size());
+        } else {
+          index.folders.entry(path.clone()).or_default();
+        }
+        parent = path;
+      }
+    }
+    Ok(index)
   }
 
-  /// Set the window's default size.
-  pub fn default_size(mut self, default_size: impl Into<Vec2>) -> Self {
-    self.default_size = default_size.into();
-    self
+  /// The entry at `inner` in the archive, shown at `path`.
+  fn entry(&self, path: PathBuf, inner: &Path) -> FileInfo {
+    match (self.folders.contains_key(inner), self.files.get(inner)) {
+      (true, _) => FileInfo::virtual_dir(path),
+      (false, Some(&size)) => FileInfo::virtual_file(path, size, None),
+      (false, None) => FileInfo::default(),
+    }
   }
+}
 
-  /// Enable/disable resizing the window. Default is `true`.
-  pub fn resizable(mut self, resizable: bool) -> Self {
-    self.resizable = resizable;
-    self
+#[cfg(feature = "zip")]
+impl ZipFileSystem {
+  /// Split `path` into the archive containing it and the path inside of the archive.
+  fn split<'a>(&self, path: &'a Path) -> Option<(&'a Path, &'a Path)> {
+    let archives = self.archives();
+    let archive = path
+      .ancestors()
+      .filter(|ancestor| {
+        is_zip(ancestor) && (archives.contains_key(*ancestor) || ancestor.is_file())
+      })
+      .last()?;
+    Some((archive, path.strip_prefix(archive).ok()?))
   }
 
-  /// Show the Rename button. Default is `true`.
-  pub fn show_rename(mut self, rename: bool) -> Self {
-    self.rename = rename;
+  /// The archives read so far. A panic while they were locked doesn't leave them unusable.
+  fn archives(&self) -> std::sync::MutexGuard<'_, HashMap<PathBuf, Arc<ZipIndex>>> {
     self
+      .archives
+      .lock()
+      .unwrap_or_else(|poisoned| poisoned.into_inner())
   }
 
-  /// Show the New Folder button. Default is `true`.
-  pub fn show_new_folder(mut self, new_folder: bool) -> Self {
-    self.new_folder = new_folder;
+  /// Index of `archive`, read once. With `refresh`, it is read again if the archive was modified
+  /// since.
+  fn index(&self, archive: &Path, refresh: bool) -> Result<Arc<ZipIndex>, Error> {
+    if let Some(index) = self.archives().get(archive) {
+      let modified = || fs::metadata(archive).and_then(|meta| meta.modified()).ok();
+      if !refresh || (index.modified.is_some() && index.modified == modified()) {
+        return Ok(Arc::clone(index));
+      }
+    }
+
+    // Not locked while reading, so that other calls aren't blocked by a large archive.
+    let index = Arc::new(ZipIndex::read(archive)?);
     self
+      .archives()
+      .insert(archive.to_path_buf(), Arc::clone(&index));
+    Ok(index)
   }
 
-  pub fn multi_select(mut self, multi_select: bool) -> Self {
-    self.multi_select_enabled = multi_select;
-    self
+  fn read_only() -> Error {
+    Error::new(
+      std::io::ErrorKind::PermissionDenied,
+      "ZIP archives are read-only",
+    )
   }
+}
 
-  pub fn has_multi_select(&self) -> bool {
-    self.multi_select_enabled
+#[cfg(feature = "zip")]
+impl FileSystem for ZipFileSystem {
+  fn read_dir(&self, path: &Path, lazy_metadata: bool) -> Result<Vec<FileInfo>, Error> {
+    let Some((archive, inner)) = self.split(path) else {
+      // Only archives are listed as folders, other entries keep their lazy metadata.
+      let entries = StdFileSystem.read_dir(path, lazy_metadata)?;
+      return Ok(
+        entries
+          .into_iter()
+          .map(|info| match is_zip(&info.path) {
+            true => self.entry(&info.path),
+            false => info,
+          })
+          .collect(),
+      );
+    };
+
+    let index = self.index(archive, true)?;
+    let Some(children) = index.folders.get(inner) else {
+      return Err(Error::from(std::io::ErrorKind::NotFound));
+    };
+    let entries = children
+      .iter()
+      .map(|name| index.entry(path.join(name), &inner.join(name)));
+    Ok(entries.collect())
   }
 
-  /// Show the mapped drives on Windows. Default is `true`.
-  #[cfg(windows)]
-  pub fn show_drives(mut self, drives: bool) -> Self {
-    self.show_drives = drives;
-    self
+  fn entry(&self, path: &Path) -> FileInfo {
+    let Some((archive, inner)) = self.split(path) else {
+      return StdFileSystem.entry(path);
+    };
+    if inner.as_os_str().is_empty() {
+      return FileInfo::virtual_dir(path);
+    }
+
+    match self.index(archive, false) {
+      Ok(index) => index.entry(path.to_path_buf(), inner),
+      Err(_) => FileInfo::default(),
+    }
   }
 
-  /// Set a function to filter listed files.
-  pub fn show_files_filter(mut self, filter: Filter<PathBuf>) -> Self {
-    self.show_files_filter = filter;
-    self
+  fn check_writable(&self, path: &Path) -> Result<(), Error> {
+    match self.split(path) {
+      Some(_) => Err(Self::read_only()),
+      None => StdFileSystem.check_writable(path),
+    }
   }
 
-  /// Set a function to filter the selected filename.
-  pub fn filename_filter(mut self, filter: Filter<String>) -> Self {
-    self.filename_filter = filter;
-    self
+  fn create_dir(&self, path: &Path) -> Result<(), Error> {
+    match self.split(path) {
+      Some(_) => Err(Self::read_only()),
+      None => StdFileSystem.create_dir(path),
+    }
   }
 
-  /// Set to true in order to keep this window on top of other windows. Default is `false`.
-  pub fn keep_on_top(mut self, keep_on_top: bool) -> Self {
-    self.keep_on_top = keep_on_top;
-    self
+  fn rename(&self, from: &Path, to: &Path) -> Result<(), Error> {
+    match self.split(from).or(self.split(to)) {
+      Some(_) => Err(Self::read_only()),
+      None => StdFileSystem.rename(from, to),
+    }
   }
 
-  /// Set to true in order to show system files. Default is `false`.
-  pub fn show_system_files(mut self, show_system_files: bool) -> Self {
-    self.show_system_files = show_system_files;
-    self
+  fn remove(&self, path: &Path) -> Result<(), Error> {
+    match self.split(path) {
+      Some(_) => Err(Self::read_only()),
+      None => StdFileSystem.remove(path),
+    }
   }
 
-  /// Get the dialog type.
-  pub fn dialog_type(&self) -> DialogType {
-    self.dialog_type
+  fn remove_all(&self, path: &Path) -> Result<(), Error> {
+    match self.split(path) {
+      Some(_) => Err(Self::read_only()),
+      None => StdFileSystem.remove_all(path),
+    }
   }
 
-  /// Get the window's visibility.
-  pub fn visible(&self) -> bool {
-    self.state == State::Open
+  fn canonicalize(&self, path: &Path) -> Result<PathBuf, Error> {
+    match self.split(path) {
+      Some(_) => Ok(path.to_path_buf()),
+      None => StdFileSystem.canonicalize(path),
+    }
   }
 
-  /// Opens the dialog.
-  pub fn open(&mut self) {
-    self.state = State::Open;
-    self.refresh();
+  #[cfg(feature = "trash")]
+  fn trash(&self, path: &Path) -> Result<(), Error> {
+    match self.split(path) {
+      Some(_) => Err(Self::read_only()),
+      None => StdFileSystem.trash(path),
+    }
   }
+}
 
-  /// Resulting file path.
-  pub fn path(&self) -> Option<&Path> {
-    self.selected_file.as_ref().map(|info| info.path.as_path())
+/// Function that is called with the result of each filesystem operation.
+pub type FsEventHandler = Box<dyn FnMut(FsEvent) + Send + Sync + 'static>;
+
+/// Function that shows a row of the file list and returns its response, which is clicked to
+/// select the entry.
+pub type RowRenderer =
+  Box<dyn Fn(&mut Ui, &FileInfoView) -> egui::Response + Send + Sync + 'static>;
+
+#[derive(Clone, Copy, Debug)]
+/// An entry of the file list as it is shown, passed to a [`RowRenderer`].
+pub struct FileInfoView<'a> {
+  info: &'a FileInfo,
+  label: &'a str,
+  selected: bool,
+}
+
+impl<'a> FileInfoView<'a> {
+  /// The entry.
+  pub fn info(&self) -> &'a FileInfo {
+    self.info
   }
 
-  /// Retrieves multi selection as a vector.
-  pub fn selection(&self) -> Vec<&Path> {
-    match self.files {
-      Ok(ref files) => files
-        .iter()
-        .filter_map(|info| {
-          if info.selected {
-            Some(info.path.as_path())
-          } else {
-            None
-          }
-        })
-        .collect(),
-      Err(_) => Vec::new(),
-    }
+  /// Path of the entry.
+  pub fn path(&self) -> &'a Path {
+    &self.info.path
   }
 
-  /// Currently mounted directory that is being shown in the dialog box
-  pub fn directory(&self) -> &Path {
-    self.path.as_path()
+  /// Returns true, if the entry is a directory.
+  pub fn is_dir(&self) -> bool {
+    self.info.is_dir()
   }
 
-  /// Set the dialog's current opened path
-  pub fn set_path(&mut self, path: impl Into<PathBuf>) {
-    self.path = path.into();
-    self.refresh();
+  /// Size of the file in bytes, `0` for folders or if the metadata can't be read.
+  pub fn size(&self) -> u64 {
+    self.info.size()
   }
 
-  /// Dialog state.
-  pub fn state(&self) -> State {
-    self.state
+  /// Returns true, if the entry is selected, in single and multi-select mode.
+  pub fn is_selected(&self) -> bool {
+    self.selected
   }
 
-  /// Returns true, if the file selection was confirmed.
-  pub fn selected(&self) -> bool {
-    self.state == State::Selected
+  /// Text the dialog shows for the entry, with its icon if icons are shown.
+  pub fn label(&self) -> &'a str {
+    self.label
   }
+}
 
-  fn open_selected(&mut self) {
-    if let Some(info) = &self.selected_file {
-      if info.is_dir() {
-        self.set_path(info.path.clone());
-      } else if self.dialog_type == DialogType::OpenFile {
-        self.confirm();
-      }
-    } else if self.multi_select_enabled && self.dialog_type == DialogType::OpenFile {
-      self.confirm();
+/// Number of seconds a toast is shown.
+const TOAST_DURATION: f64 = 3.0;
+
+#[derive(Clone, Copy, Debug)]
+/// Column right of the entry names.
+enum Column {
+  Size,
+  Modified,
+  #[cfg(unix)]
+  Permissions,
+}
+
+impl Column {
+  /// Width of the column, including the space to the column on its left.
+  fn width(self) -> f32 {
+    match self {
+      Column::Size => SIZE_COLUMN_WIDTH,
+      Column::Modified => MODIFIED_COLUMN_WIDTH,
+      #[cfg(unix)]
+      Column::Permissions => PERMISSIONS_COLUMN_WIDTH,
     }
   }
+}
 
-  fn confirm(&mut self) {
-    self.state = State::Selected;
+/// Width of the size column.
+const SIZE_COLUMN_WIDTH: f32 = 80.0;
+
+/// Width of the modification time column in the details view.
+const MODIFIED_COLUMN_WIDTH: f32 = 120.0;
+
+/// Width of the permissions column in the details view.
+#[cfg(unix)]
+const PERMISSIONS_COLUMN_WIDTH: f32 = 90.0;
+
+/// Maximum number of thumbnails decoded per frame, so that scrolling stays responsive.
+#[cfg(feature = "image")]
+const THUMBNAILS_PER_FRAME: usize = 2;
+
+/// Maximum width and height of decoded previews.
+#[cfg(feature = "image")]
+const PREVIEW_SIZE: f32 = 512.0;
+
+/// Seconds without typing after which type-ahead starts over.
+const TYPE_AHEAD_TIMEOUT: f64 = 1.0;
+
+/// Lists shorter than this are rendered fully, even if virtualization is enabled.
+const VIRTUALIZE_MIN_ROWS: usize = 256;
+
+/// Seconds without further changes on disk before the listing is refreshed.
+#[cfg(feature = "watch")]
+const WATCH_DEBOUNCE: f64 = 0.3;
+
+/// Function that returns `true` if the path is accepted.
+pub type Filter<T> = Box<dyn Fn(&<T as Deref>::Target) -> bool + Send + Sync + 'static>;
+
+impl FileDialog {
+  /// Create dialog that prompts the user to select a folder.
+  pub fn select_folder(initial_path: Option<PathBuf>) -> Self {
+    FileDialog::new(DialogType::SelectFolder, initial_path)
   }
 
-  fn refresh(&mut self) {
-    self.files = self.read_folder();
-    self.path_edit = String::from(self.path.to_str().unwrap_or_default());
-    self.select(None);
-    self.selected_file = None;
+  /// Create dialog that prompts the user to open a file.
+  pub fn open_file(initial_path: Option<PathBuf>) -> Self {
+    FileDialog::new(DialogType::OpenFile, initial_path)
   }
 
-  fn select(&mut self, file: Option<FileInfo>) {
-    if let Some(info) = &file {
-      if !info.is_dir() {
-        get_file_name(info).clone_into(&mut self.filename_edit);
-      }
-    }
-    self.selected_file = file;
+  /// Create dialog that prompts the user to save a file.
+  pub fn save_file(initial_path: Option<PathBuf>) -> Self {
+    FileDialog::new(DialogType::SaveFile, initial_path)
   }
 
-  fn select_reset_multi(&mut self, idx: usize) {
-    if let Ok(files) = &mut self.files {
-      let selected_val = files[idx].selected;
-      for file in files.iter_mut() {
-        file.selected = false;
-      }
-      files[idx].selected = !selected_val;
-      self.range_start = Some(idx);
-    }
+  /// Create dialog of the given type, e.g. when the type is only known at runtime.
+  pub fn with_type(dialog_type: DialogType, initial_path: Option<PathBuf>) -> Self {
+    FileDialog::new(dialog_type, initial_path)
   }
 
-  fn select_switch_multi(&mut self, idx: usize) {
-    if let Ok(files) = &mut self.files {
-      files[idx].selected = !files[idx].selected;
-      if files[idx].selected {
-        self.range_start = Some(idx);
-      } else {
-        self.range_start = None;
-      }
-    } else {
-      self.range_start = None;
+  /// Create a dialog with the settings of `state`, e.g. from the last time the app was run.
+  pub fn from_state(dialog_type: DialogType, state: FileDialogState) -> Self {
+    let mut dialog = Self::new(dialog_type, Some(state.path))
+      .default_size(state.window_size)
+      .default_view_mode(state.view_mode)
+      .default_sort(state.sort_by, state.sort_reverse)
+      .show_hidden(state.show_hidden);
+    dialog.refresh();
+    dialog
+  }
+
+  /// The settings of the dialog that can be restored with [`FileDialog::from_state`].
+  pub fn to_state(&self) -> FileDialogState {
+    FileDialogState {
+      path: self.path.clone(),
+      sort_by: self.sort_by,
+      sort_reverse: self.sort_reverse,
+      show_hidden: self.show_hidden,
+      view_mode: self.view_mode,
+      window_size: self.window_size.into(),
     }
   }
 
-  fn select_range(&mut self, idx: usize) {
-    if let Ok(files) = &mut self.files {
-      if let Some(range_start) = self.range_start {
-        let range = cmp::min(idx, range_start)..=cmp::max(idx, range_start);
-        for i in range {
-          files[i].selected = true;
-        }
-      }
+  /// Constructs new file dialog. If no `initial_path` is passed,`env::current_dir` is used.
+  fn new(dialog_type: DialogType, initial_path: Option<PathBuf>) -> Self {
+    let initial_path = initial_path.unwrap_or_else(|| env::current_dir().unwrap_or_default());
+    let (path, filename_edit) = split_initial_path(&StdFileSystem, dialog_type, &initial_path);
+
+    let path_edit = path.to_str().unwrap_or_default().to_string();
+    Self {
+      initial_path,
+      path,
+      path_edit,
+      path_suggestions: Vec::new(),
+      display_separator: path::MAIN_SEPARATOR,
+      selected_file: None,
+      filename_edit,
+      focus_filename: false,
+      focus_list: false,
+      title: default_title(dialog_type, &FileDialogLabels::default()).to_string(),
+      title_icon: true,
+      labels: FileDialogLabels::default(),
+      empty_text: "This folder is empty".to_string(),
+      icons: Icons::default(),
+      show_icons: true,
+      files: Ok(Vec::new()),
+      pending_listing: None,
+      loading: false,
+      keep_selection: Vec::new(),
+      state: State::Closed,
+      confirmed: false,
+      consumed: false,
+      confirm_overwrite: true,
+      unique_on_conflict: false,
+      confirm_cancel: false,
+      prompt: None,
+      dialog_type,
+      last_error: None,
+      toasts: Vec::new(),
+      recent: VecDeque::new(),
+      recent_limit: 10,
+      cache: VecDeque::new(),
+      cache_limit: 0,
+      show_sidebar: false,
+      show_tree: false,
+      tree: HashMap::new(),
+      tree_path: None,
+      bookmarks: Vec::new(),
+      quick_access: Vec::new(),
+      path_bar_mode: PathBarMode::default(),
+      editing_path: false,
+      view_mode: ViewMode::default(),
+      grid_columns: 4,
+      thumbnail_size: egui::vec2(64.0, 64.0),
+      #[cfg(feature = "image")]
+      thumbnails: HashMap::new(),
+      show_text_preview: false,
+      preview_max_bytes: 16 * 1024,
+      text_preview: None,
+      #[cfg(feature = "image")]
+      show_preview: false,
+      #[cfg(feature = "image")]
+      preview: None,
+      sort_by: SortKey::default(),
+      sort_reverse: false,
+
+      id: None,
+      current_pos: None,
+      default_pos: None,
+      default_size: egui::vec2(512.0, 512.0),
+      window_size: egui::vec2(512.0, 512.0),
+      default_extension: None,
+      fallback_path: None,
+      anchor: None,
+      show_files_filter: Box::new(|_| true),
+      show_dirs_filter: Box::new(|_| true),
+      filters: Vec::new(),
+      active_filter: 0,
+      filename_filter: Box::new(|_| true),
+      fs_event_handler: None,
+      row_renderer: None,
+      file_system: Arc::new(StdFileSystem),
+      resizable: true,
+      rename: true,
+      inline_rename: None,
+      delete: false,
+      delete_mode: DeleteMode::default(),
+      delete_recursive: false,
+      new_folder: true,
+
+      #[cfg(windows)]
+      show_drives: true,
+
+      #[cfg(windows)]
+      known_folders: Vec::new(),
+
+      #[cfg(unix)]
+      show_mounts: false,
+
+      #[cfg(unix)]
+      show_permissions: false,
+
+      #[cfg(unix)]
+      mounts: Vec::new(),
+
+      #[cfg(feature = "watch")]
+      watch: true,
+
+      #[cfg(feature = "watch")]
+      watcher: None,
+      multi_select_enabled: false,
+      selection_limits: (0, None),
+      range_start: None,
+      focused_index: None,
+      page_rows: 1,
+      type_ahead: String::new(),
+      type_ahead_time: 0.0,
+      initial_highlight: None,
+      scroll_to_row: None,
+      search_query: String::new(),
+      search_match: None,
+      visible: Vec::new(),
+      keep_on_top: false,
+      modal: false,
+      show_system_files: false,
+      show_hidden: false,
+      // Ctrl rather than Command, as macOS hides the application on Command+H.
+      show_hidden_shortcut: Some(egui::KeyboardShortcut::new(egui::Modifiers::CTRL, Key::H)),
+      check_readable: true,
+      back_on_error: None,
+      check_writable: false,
+      write_error: None,
+      follow_symlinks: false,
+      link_targets: HashMap::new(),
+      lazy_metadata: false,
+      async_loading: false,
+      virtualize: true,
+      show_parent_entry: true,
+      trim_filename: true,
+      show_size: false,
+      show_modified: false,
+      date_format: String::from("%Y-%m-%d %H:%M"),
+      date_items: parse_date_format("%Y-%m-%d %H:%M"),
+      date_display: DateDisplay::default(),
+      confirm_multi_summary: false,
+      show_multi_summary: false,
     }
   }
 
-  fn can_save(&self) -> bool {
-    !self.filename_edit.is_empty() && (self.filename_filter)(self.filename_edit.as_str())
+  /// Set the default file name.
+  pub fn default_filename(mut self, filename: impl Into<String>) -> Self {
+    self.filename_edit = filename.into();
+    self
   }
 
-  fn can_open(&self) -> bool {
-    if self.multi_select_enabled {
-      if let Ok(files) = &self.files {
-        for file in files {
-          if file.selected && (self.filename_filter)(get_file_name(file)) {
+  /// Set the window title text.
+  pub fn title(mut self, title: &str) -> Self {
+    self.title = title.to_string();
+    self
+  }
+
+  /// Set the message that is shown when the folder has no entries, or none that pass the filters.
+  /// Default is `"This folder is empty"`.
+  pub fn empty_text(mut self, text: impl Into<String>) -> Self {
+    self.empty_text = text.into();
+    self
+  }
+
+  /// Set to false in order to not show an emoji for the dialog type before the title. Default is
+  /// `true`.
+  pub fn title_icon(mut self, title_icon: bool) -> Self {
+    self.title_icon = title_icon;
+    self
+  }
+
+  /// Set the icons of entries and buttons, e.g. in order to use glyphs of another font. Default is
+  /// emoji of the default egui fonts.
+  pub fn icons(mut self, icons: Icons) -> Self {
+    self.icons = icons;
+    self
+  }
+
+  /// Set to false in order to not show icons, e.g. if the font doesn't have them. Buttons then
+  /// show text instead. Default is `true`.
+  pub fn show_icons(mut self, show_icons: bool) -> Self {
+    self.show_icons = show_icons;
+    self
+  }
+
+  /// Set the text of all buttons and labels, e.g. for a translation. This also sets the title,
+  /// so call [`FileDialog::title`] afterwards to change it. Default is English.
+  pub fn labels(mut self, labels: FileDialogLabels) -> Self {
+    self.title = default_title(self.dialog_type, &labels).to_string();
+    // Rename the default quick access shortcuts if they were already added.
+    let names = |labels: &FileDialogLabels| {
+      [
+        labels.home,
+        labels.desktop,
+        labels.documents,
+        labels.downloads,
+      ]
+    };
+    for (name, _) in &mut self.quick_access {
+      if let Some(pos) = names(&self.labels).iter().position(|old| old == name) {
+        *name = names(&labels)[pos].to_string();
+      }
+    }
+    self.labels = labels;
+    self
+  }
+
+  /// Set the open button text.
+  pub fn open_button_text(mut self, text: &'static str) -> Self {
+    self.labels.open_button = text;
+    self
+  }
+
+  /// Set the save button text.
+  pub fn save_button_text(mut self, text: &'static str) -> Self {
+    self.labels.save_button = text;
+    self
+  }
+
+  /// Set the cancel button text.
+  pub fn cancel_button_text(mut self, text: &'static str) -> Self {
+    self.labels.cancel_button = text;
+    self
+  }
+
+  /// Set the new folder button text.
+  pub fn new_folder_button_text(mut self, text: &'static str) -> Self {
+    self.labels.new_folder_button = text;
+    self
+  }
+
+  /// Set the suggested name of new folders.
+  pub fn new_folder_name_text(mut self, text: &'static str) -> Self {
+    self.labels.new_folder_name = text;
+    self
+  }
+
+  /// Set the refresh button hover text.
+  pub fn refresh_button_hover_text(mut self, text: &'static str) -> Self {
+    self.labels.refresh_button_hover = text;
+    self
+  }
+
+  /// Set the parent folder button hover text.
+  pub fn parent_folder_button_hover_text(mut self, text: &'static str) -> Self {
+    self.labels.parent_folder_button_hover = text;
+    self
+  }
+
+  /// Set the rename button text.
+  pub fn rename_button_text(mut self, text: &'static str) -> Self {
+    self.labels.rename_button = text;
+    self
+  }
+
+  /// Set the file label text.
+  pub fn file_label_text(mut self, text: &'static str) -> Self {
+    self.labels.file_label = text;
+    self
+  }
+
+  /// Set the show hidden checkbox text.
+  pub fn show_hidden_checkbox_text(mut self, text: &'static str) -> Self {
+    self.labels.show_hidden_checkbox = text;
+    self
+  }
+
+  /// Set the separator used to display paths, e.g. `/` on all platforms. Filesystem operations
+  /// still use the native separator. Default is the native separator.
+  pub fn display_separator(mut self, separator: char) -> Self {
+    self.display_separator = separator;
+    self.path_edit = self.display_path(&self.path);
+    self
+  }
+
+  /// Set the extension, without the dot, that is appended when saving a file name without one,
+  /// e.g. "report" is saved as "report.txt". Default is none.
+  pub fn default_extension(mut self, extension: impl Into<String>) -> Self {
+    let extension = extension.into();
+    self.default_extension = Some(extension.trim_start_matches('.').to_string());
+    self
+  }
+
+  /// Set the directory that is opened when the initial path, e.g. one stored from a previous
+  /// run, no longer exists. Default is its nearest existing parent folder, or else the working
+  /// directory.
+  pub fn fallback_path(mut self, path: impl Into<PathBuf>) -> Self {
+    self.fallback_path = Some(path.into());
+    self
+  }
+
+  /// Set the number of recently visited directories listed in the toolbar. Default is `10`.
+  pub fn recent_limit(mut self, limit: usize) -> Self {
+    self.recent_limit = limit;
+    self.recent.truncate(limit);
+    self
+  }
+
+  /// Set the number of directory listings that are kept in memory, so that going back to a
+  /// directory doesn't read it again. A listing is read again once the directory's modification
+  /// time changes, which misses changes to the files in it, and always if the modification time is
+  /// unknown or `async_loading` is set. Refreshing reads the current directory again. Default is
+  /// `0`, which disables caching.
+  pub fn cache_limit(mut self, limit: usize) -> Self {
+    self.cache_limit = limit;
+    self.cache.truncate(limit);
+    self
+  }
+
+  /// Show a sidebar with bookmarked directories, which the user can add and remove.
+  /// Default is `false`.
+  pub fn show_sidebar(mut self, show_sidebar: bool) -> Self {
+    self.show_sidebar = show_sidebar;
+    self
+  }
+
+  /// Show a tree of folders next to the file list. Folders are only read when they are expanded.
+  /// Default is `false`.
+  pub fn show_tree(mut self, show_tree: bool) -> Self {
+    self.show_tree = show_tree;
+    self
+  }
+
+  /// Show shortcuts to the home, desktop, documents and downloads directories in the sidebar.
+  /// Directories that don't exist are omitted. Default is `false`.
+  pub fn show_quick_access(mut self, show_quick_access: bool) -> Self {
+    self.quick_access = match show_quick_access {
+      true => get_quick_access(&self.labels),
+      false => Vec::new(),
+    };
+    self
+  }
+
+  /// Replace the quick access shortcuts with `(name, path)` pairs. Directories that don't exist
+  /// are omitted.
+  pub fn quick_access(mut self, quick_access: Vec<(String, PathBuf)>) -> Self {
+    self.quick_access = quick_access
+      .into_iter()
+      .filter(|(_, path)| path.is_dir())
+      .collect();
+    self
+  }
+
+  /// Set how the current path is shown. Default is [`PathBarMode::Editable`].
+  pub fn path_bar_mode(mut self, path_bar_mode: PathBarMode) -> Self {
+    self.path_bar_mode = path_bar_mode;
+    self
+  }
+
+  /// Set the initial view mode. It can be changed from the toolbar or with `Ctrl+1`, `Ctrl+2`,
+  /// etc. Default is [`ViewMode::List`].
+  pub fn default_view_mode(mut self, view_mode: ViewMode) -> Self {
+    if let ViewMode::Grid { columns } = view_mode {
+      self.grid_columns = columns;
+    }
+    self.view_mode = view_mode;
+    self
+  }
+
+  /// Set the size of thumbnails in the grid view. Default is 64 by 64.
+  pub fn thumbnail_size(mut self, thumbnail_size: impl Into<Vec2>) -> Self {
+    self.thumbnail_size = thumbnail_size.into();
+    self
+  }
+
+  /// Set the initial sort order of the file list. It can be changed by clicking the list header.
+  /// Default is by name in ascending order.
+  pub fn default_sort(mut self, sort_by: SortKey, reverse: bool) -> Self {
+    self.sort_by = sort_by;
+    self.sort_reverse = reverse;
+    self
+  }
+
+  /// Set to true in order to show the start of the selected text file next to the file list.
+  /// Default is `false`.
+  pub fn show_text_preview(mut self, show_text_preview: bool) -> Self {
+    self.show_text_preview = show_text_preview;
+    self
+  }
+
+  /// Set the maximum number of bytes read from a file for the text preview. Default is 16 KiB.
+  pub fn preview_max_bytes(mut self, max_bytes: usize) -> Self {
+    self.preview_max_bytes = max_bytes;
+    self
+  }
+
+  /// Set to true in order to show a preview of the selected image next to the file list.
+  /// Default is `false`.
+  #[cfg(feature = "image")]
+  pub fn show_preview(mut self, show_preview: bool) -> Self {
+    self.show_preview = show_preview;
+    self
+  }
+
+  /// Set the window ID.
+  pub fn id(mut self, id: impl Into<Id>) -> Self {
+    self.id = Some(id.into());
+    self
+  }
+
+  /// Set the window anchor.
+  pub fn anchor(mut self, align: Align2, offset: impl Into<Vec2>) -> Self {
+    self.anchor = Some((align, offset.into()));
+    self
+  }
+
+  /// Set the window's current position.
+  pub fn current_pos(mut self, current_pos: impl Into<Pos2>) -> Self {
+    self.current_pos = Some(current_pos.into());
+    self
+  }
+
+  /// Set the window's default position.
+  pub fn default_pos(mut self, default_pos: impl Into<Pos2>) -> Self {
+    self.default_pos = Some(default_pos.into());
+    self
+  }
+
+  /// Set the window's default size.
+  pub fn default_size(mut self, default_size: impl Into<Vec2>) -> Self {
+    self.default_size = default_size.into();
+    self.window_size = self.default_size;
+    self
+  }
+
+  /// Enable/disable resizing the window. Default is `true`.
+  pub fn resizable(mut self, resizable: bool) -> Self {
+    self.resizable = resizable;
+    self
+  }
+
+  /// Show the Rename button. Default is `true`.
+  pub fn show_rename(mut self, rename: bool) -> Self {
+    self.rename = rename;
+    self
+  }
+
+  /// Show the Delete button, which deletes the selected file or empty folder after asking for
+  /// confirmation. See [`FileDialog::delete_recursive`] for other folders. Default is `false`.
+  pub fn show_delete(mut self, delete: bool) -> Self {
+    self.delete = delete;
+    self
+  }
+
+  /// Set how the Delete button removes files. Default is [`DeleteMode::Trash`] with the `trash`
+  /// feature and [`DeleteMode::Permanent`] otherwise.
+  pub fn delete_mode(mut self, delete_mode: DeleteMode) -> Self {
+    self.delete_mode = delete_mode;
+    self
+  }
+
+  /// Set to true in order to allow deleting folders that aren't empty, along with everything in
+  /// them. The confirmation then tells how many items the folder has. Default is `false`.
+  pub fn delete_recursive(mut self, delete_recursive: bool) -> Self {
+    self.delete_recursive = delete_recursive;
+    self
+  }
+
+  /// Show the New Folder button. Default is `true`.
+  pub fn show_new_folder(mut self, new_folder: bool) -> Self {
+    self.new_folder = new_folder;
+    self
+  }
+
+  pub fn multi_select(mut self, multi_select: bool) -> Self {
+    self.multi_select_enabled = multi_select;
+    self
+  }
+
+  pub fn has_multi_select(&self) -> bool {
+    self.multi_select_enabled
+  }
+
+  /// Require at least `min` and at most `max` entries in a multi selection. Default is no
+  /// limits.
+  pub fn selection_limits(mut self, min: usize, max: Option<usize>) -> Self {
+    self.selection_limits = (min, max);
+    self
+  }
+
+  /// Show a summary of the selected files with Proceed/Back buttons before confirming a multi
+  /// selection. Default is `false`.
+  pub fn confirm_multi_summary(mut self, confirm_multi_summary: bool) -> Self {
+    self.confirm_multi_summary = confirm_multi_summary;
+    self
+  }
+
+  /// Highlight the entry at this index of the listing when the dialog is first opened, e.g. to
+  /// restore a previous session. An index outside of the listing is ignored.
+  pub fn initial_highlight(mut self, idx: usize) -> Self {
+    self.initial_highlight = Some(idx);
+    self
+  }
+
+  /// Show the mapped drives on Windows. Default is `true`.
+  #[cfg(windows)]
+  pub fn show_drives(mut self, drives: bool) -> Self {
+    self.show_drives = drives;
+    self
+  }
+
+  /// Show a column with the permissions of the entries, like `rwxr-xr-x`, in the details view on
+  /// unix systems. Default is `false`.
+  #[cfg(unix)]
+  pub fn show_permissions(mut self, show_permissions: bool) -> Self {
+    self.show_permissions = show_permissions;
+    self
+  }
+
+  /// Set to false in order to not refresh the listing when the current directory changes on
+  /// disk. Default is `true`.
+  #[cfg(feature = "watch")]
+  pub fn watch(mut self, watch: bool) -> Self {
+    self.watch = watch;
+    self
+  }
+
+  /// Show the volumes mounted under `/media`, `/mnt` and `/Volumes` in the sidebar on unix
+  /// systems. Default is `false`.
+  #[cfg(unix)]
+  pub fn show_mounts(mut self, mounts: bool) -> Self {
+    self.show_mounts = mounts;
+    self.mounts = match mounts {
+      true => get_mounts(),
+      false => Vec::new(),
+    };
+    self
+  }
+
+  /// Show shortcuts to the hidden known folders (AppData, ProgramData, etc.) on Windows.
+  /// Folders that can't be resolved are skipped. Default is `false`.
+  #[cfg(windows)]
+  pub fn show_known_folders(mut self, known_folders: bool) -> Self {
+    self.known_folders = match known_folders {
+      true => get_known_folders(),
+      false => Vec::new(),
+    };
+    self
+  }
+
+  /// Set to false in order to save over an existing file without asking. Default is `true`.
+  pub fn confirm_overwrite(mut self, confirm_overwrite: bool) -> Self {
+    self.confirm_overwrite = confirm_overwrite;
+    self
+  }
+
+  /// Set to true in order to save under the first free name like "report (1).txt" instead of over
+  /// an existing file. The overwrite confirmation isn't needed then. Default is `false`.
+  pub fn unique_on_conflict(mut self, unique_on_conflict: bool) -> Self {
+    self.unique_on_conflict = unique_on_conflict;
+    self
+  }
+
+  /// Set to true in order to ask before cancelling a save dialog whose file name field isn't empty.
+  /// Default is `false`.
+  pub fn confirm_cancel(mut self, confirm_cancel: bool) -> Self {
+    self.confirm_cancel = confirm_cancel;
+    self
+  }
+
+  /// Set to true in order to show the size of files next to their name. Default is `false`.
+  pub fn show_size(mut self, show_size: bool) -> Self {
+    self.show_size = show_size;
+    self
+  }
+
+  /// Set to true in order to show the modification time of files next to their name. Default is
+  /// `false`.
+  pub fn show_modified(mut self, show_modified: bool) -> Self {
+    self.show_modified = show_modified;
+    self
+  }
+
+  /// Set the strftime-style format of the modification time, e.g. `"%d.%m.%Y"`. Default is
+  /// `"%Y-%m-%d %H:%M"`.
+  pub fn date_format(mut self, date_format: impl Into<String>) -> Self {
+    self.date_format = date_format.into();
+    self.date_items = parse_date_format(&self.date_format);
+    self
+  }
+
+  /// Set how modification times are displayed. Default is [`DateDisplay::Absolute`].
+  pub fn date_display(mut self, date_display: DateDisplay) -> Self {
+    self.date_display = date_display;
+    self
+  }
+
+  /// Set to true in order to remove leading and trailing whitespace from the typed file name.
+  /// Default is `true`.
+  pub fn trim_filename(mut self, trim_filename: bool) -> Self {
+    self.trim_filename = trim_filename;
+    self
+  }
+
+  /// Set a function to filter listed files.
+  pub fn show_files_filter(mut self, filter: Filter<PathBuf>) -> Self {
+    self.show_files_filter = filter;
+    self
+  }
+
+  /// Set a function to filter listed folders, e.g. to hide `node_modules`. Drives are always
+  /// listed.
+  pub fn show_dirs_filter(mut self, filter: Filter<PathBuf>) -> Self {
+    self.show_dirs_filter = filter;
+    self
+  }
+
+  /// A filter for [`FileDialog::show_files_filter`] that accepts files whose name matches a
+  /// shell-style pattern with `*`, `?` and `[...]`, e.g. `"*.rs"`. An invalid pattern accepts no
+  /// files.
+  pub fn filter_glob(pattern: &str) -> Filter<PathBuf> {
+    match glob::Pattern::new(pattern) {
+      Ok(pattern) => Box::new(move |path: &Path| {
+        path
+          .file_name()
+          .is_some_and(|name| pattern.matches(&name.to_string_lossy()))
+      }),
+      Err(_) => Box::new(|_| false),
+    }
+  }
+
+  /// A filter for [`FileDialog::show_files_filter`] that accepts files whose name matches a
+  /// regular expression, e.g. `r"^IMG_\d+\.jpg$"`.
+  #[cfg(feature = "regex")]
+  pub fn filter_regex(pattern: &str) -> Result<Filter<PathBuf>, regex::Error> {
+    let regex = regex::Regex::new(pattern)?;
+    Ok(Box::new(move |path: &Path| {
+      path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| regex.is_match(name))
+    }))
+  }
+
+  /// Add a named filter, e.g. "Images", that the user can choose in a drop-down next to the file
+  /// name. The first one is active initially. It applies in addition to
+  /// [`FileDialog::show_files_filter`].
+  pub fn add_filter(mut self, name: impl Into<String>, filter: Filter<PathBuf>) -> Self {
+    self.filters.push((name.into(), filter, None));
+    self
+  }
+
+  /// Add named filters for file types, e.g. `("Images", vec!["png", "jpg"])`, like
+  /// [`FileDialog::add_filter`] with [`FileDialog::filter_extensions`]. An empty list of
+  /// extensions accepts all files. In save dialogs, choosing a type sets the default extension to
+  /// its first one.
+  pub fn file_types<N: Into<String>, E: AsRef<str>>(
+    mut self,
+    file_types: impl IntoIterator<Item = (N, Vec<E>)>,
+  ) -> Self {
+    for (name, extensions) in file_types {
+      let extensions: Vec<&str> = extensions.iter().map(AsRef::as_ref).collect();
+      let extension = extensions
+        .first()
+        .map(|extension| extension.trim_start_matches('.').to_string());
+      let filter = Self::filter_extensions(&extensions);
+      self.filters.push((name.into(), filter, extension));
+    }
+    if self.default_extension.is_none() {
+      self.set_active_filter(self.active_filter);
+    }
+    self
+  }
+
+  /// Name of the named filter chosen by the user, if any were added.
+  pub fn active_filter_name(&self) -> Option<&str> {
+    let (name, _, _) = self.filters.get(self.active_filter)?;
+    Some(name)
+  }
+
+  /// Choose the named filter at `idx`, in save dialogs along with the extension of its file type.
+  fn set_active_filter(&mut self, idx: usize) {
+    self.active_filter = idx;
+    if self.dialog_type == DialogType::SaveFile {
+      if let Some((_, _, Some(extension))) = self.filters.get(idx) {
+        self.default_extension = Some(extension.clone());
+      }
+    }
+    self.apply_search();
+  }
+
+  /// A filter for [`FileDialog::show_files_filter`] that accepts files with one of the
+  /// extensions, ignoring case, e.g. `&["png", "jpg"]`. An empty list accepts all files.
+  pub fn filter_extensions(extensions: &[&str]) -> Filter<PathBuf> {
+    let extensions: Vec<String> = extensions
+      .iter()
+      .map(|extension| extension.trim_start_matches('.').to_lowercase())
+      .collect();
+    Box::new(move |path: &Path| {
+      extensions.is_empty()
+        || path.extension().is_some_and(|extension| {
+          let extension = extension.to_string_lossy().to_lowercase();
+          extensions.contains(&extension)
+        })
+    })
+  }
+
+  /// Set a function to filter the selected filename.
+  pub fn filename_filter(mut self, filter: Filter<String>) -> Self {
+    self.filename_filter = filter;
+    self
+  }
+
+  /// Set a function that is notified of folders being created, renamed, etc. through the
+  /// dialog, and of these operations failing.
+  pub fn on_fs_event(mut self, handler: FsEventHandler) -> Self {
+    self.fs_event_handler = Some(handler);
+    self
+  }
+
+  /// Set a function that shows the rows of the list and details views instead of the default
+  /// label. Rows should be one line of text high. Clicks on the returned response select and
+  /// open the entry as usual.
+  pub fn row_renderer(mut self, renderer: RowRenderer) -> Self {
+    self.row_renderer = Some(renderer);
+    self
+  }
+
+  /// Set the file system that is listed and modified. Default is [`StdFileSystem`].
+  pub fn file_system(mut self, file_system: impl FileSystem + 'static) -> Self {
+    self.file_system = Arc::new(file_system);
+
+    // Tell again whether the initial path is a file, unless it was changed since.
+    let unchanged = match self.filename_edit.is_empty() {
+      true => self.path == self.initial_path,
+      false => self.path.join(&self.filename_edit) == self.initial_path,
+    };
+    if unchanged {
+      let file_system = &*self.file_system;
+      (self.path, self.filename_edit) =
+        split_initial_path(file_system, self.dialog_type, &self.initial_path);
+      self.path_edit = self.path.to_str().unwrap_or_default().to_string();
+    }
+    self
+  }
+
+  /// Set to true in order to keep this window on top of other windows. Default is `false`.
+  pub fn keep_on_top(mut self, keep_on_top: bool) -> Self {
+    self.keep_on_top = keep_on_top;
+    self
+  }
+
+  /// Set to true in order to dim the rest of the UI and ignore clicks on it while the dialog is
+  /// open. Default is `false`.
+  pub fn modal(mut self, modal: bool) -> Self {
+    self.modal = modal;
+    self
+  }
+
+  /// Set to true in order to show hidden files, i.e. files whose name starts with a dot and on
+  /// Windows also files with the hidden or system attribute. Default is `false`.
+  pub fn show_hidden(mut self, show_hidden: bool) -> Self {
+    self.show_hidden = show_hidden;
+    self
+  }
+
+  /// Set the shortcut that shows or hides hidden files, or `None` to turn it off. Default is
+  /// `Ctrl+H`, also on macOS.
+  pub fn show_hidden_shortcut(mut self, shortcut: Option<egui::KeyboardShortcut>) -> Self {
+    self.show_hidden_shortcut = shortcut;
+    self
+  }
+
+  /// Set to true in order to show system files. Default is `false`.
+  pub fn show_system_files(mut self, show_system_files: bool) -> Self {
+    self.show_system_files = show_system_files;
+    self
+  }
+
+  /// Set to true in order to check that a folder can be read before navigating into it. This
+  /// costs an extra read of the folder, unless `async_loading` is set, in which case the dialog
+  /// goes back once the background read fails. Default is `true`.
+  pub fn check_readable(mut self, check_readable: bool) -> Self {
+    self.check_readable = check_readable;
+    self
+  }
+
+  /// Set to true in order to check that the current folder is writable in save dialogs. The check
+  /// creates and removes a file once per folder, which also changes the folder's modification
+  /// time. Default is `false`.
+  pub fn check_writable(mut self, check_writable: bool) -> Self {
+    self.check_writable = check_writable;
+    self
+  }
+
+  /// Set to true in order to resolve symbolic links to their targets when browsing into linked
+  /// folders and in the result. When false, the paths of the links are kept and links are sorted
+  /// by their own size and modification time. Default is `false`.
+  pub fn follow_symlinks(mut self, follow_symlinks: bool) -> Self {
+    self.follow_symlinks = follow_symlinks;
+    self
+  }
+
+  /// Set to true in order to read the metadata of listed entries only when it is needed, e.g.
+  /// when the entry is shown. This is faster for huge or network folders. Default is `false`.
+  pub fn lazy_metadata(mut self, lazy_metadata: bool) -> Self {
+    self.lazy_metadata = lazy_metadata;
+    self
+  }
+
+  /// Set to true in order to read directories on a background thread, so that slow or network
+  /// folders don't freeze the dialog. A spinner is shown until the entries arrive. Default is
+  /// `false`.
+  pub fn async_loading(mut self, async_loading: bool) -> Self {
+    self.async_loading = async_loading;
+    self
+  }
+
+  /// Set to false in order to always render every entry of the file list. When true, only the
+  /// visible rows of long lists are rendered. Default is `true`.
+  pub fn virtualize(mut self, virtualize: bool) -> Self {
+    self.virtualize = virtualize;
+    self
+  }
+
+  /// Show a ".." entry at the top of the file list that goes up a directory. Default is `true`.
+  pub fn show_parent_entry(mut self, show_parent_entry: bool) -> Self {
+    self.show_parent_entry = show_parent_entry;
+    self
+  }
+
+  /// Bookmark a directory, unless it already is.
+  pub fn add_bookmark(&mut self, path: impl Into<PathBuf>) {
+    let path = path.into();
+    if !self.bookmarks.contains(&path) {
+      self.bookmarks.push(path);
+    }
+  }
+
+  /// Remove a directory from the bookmarks.
+  pub fn remove_bookmark(&mut self, path: &Path) {
+    self.bookmarks.retain(|bookmark| bookmark != path);
+  }
+
+  /// Bookmarked directories, e.g. to store them for the next time the dialog is opened.
+  pub fn bookmarks(&self) -> &[PathBuf] {
+    &self.bookmarks
+  }
+
+  /// Take the error of the last failed operation (creating a folder, renaming, deleting or
+  /// opening a folder), which also dismisses it in the dialog.
+  pub fn take_error(&mut self) -> Option<String> {
+    self.last_error.take()
+  }
+
+  /// Get the dialog type.
+  pub fn dialog_type(&self) -> DialogType {
+    self.dialog_type
+  }
+
+  /// Get the current view mode.
+  pub fn view_mode(&self) -> ViewMode {
+    self.view_mode
+  }
+
+  /// Get the window's visibility.
+  pub fn visible(&self) -> bool {
+    self.state == State::Open
+  }
+
+  /// Opens the dialog. If the current directory no longer exists, the fallback path, its nearest
+  /// existing parent folder or else the working directory is opened instead.
+  pub fn open(&mut self) {
+    if !self.file_system.is_dir(&self.path) {
+      let parent = self
+        .path
+        .ancestors()
+        .find(|path| self.file_system.is_dir(path))
+        .map(Path::to_path_buf);
+      self.path = match &self.fallback_path {
+        Some(path) if self.file_system.is_dir(path) => path.clone(),
+        _ => parent.unwrap_or_else(|| env::current_dir().unwrap_or_default()),
+      };
+    }
+    self.state = State::Open;
+    self.consumed = false;
+    self.focus_list = true;
+    self.refresh();
+  }
+
+  /// Closes the dialog without changing the selection.
+  pub fn close(&mut self) {
+    self.state = State::Closed;
+
+    #[cfg(feature = "watch")]
+    {
+      self.watcher = None;
+    }
+  }
+
+  /// Deselects all entries, keeping the current directory.
+  pub fn clear_selection(&mut self) {
+    self.selected_file = None;
+    self.range_start = None;
+    if let Ok(files) = &mut self.files {
+      for file in files {
+        file.selected = false;
+      }
+    }
+  }
+
+  /// Resulting file path.
+  pub fn path(&self) -> Option<&Path> {
+    self.selected_file.as_ref().map(|info| info.path.as_path())
+  }
+
+  /// Retrieves multi selection as a vector.
+  pub fn selection(&self) -> Vec<&Path> {
+    match self.files {
+      Ok(ref files) => files
+        .iter()
+        .filter_map(|info| {
+          if info.selected {
+            let target = self.link_targets.get(&info.path);
+            Some(target.unwrap_or(&info.path).as_path())
+          } else {
+            None
+          }
+        })
+        .collect(),
+      Err(_) => Vec::new(),
+    }
+  }
+
+  /// Resulting file path, owned so it can be kept after the dialog is gone.
+  pub fn path_owned(&self) -> Option<PathBuf> {
+    self.path().map(Path::to_path_buf)
+  }
+
+  /// Retrieves multi selection as a vector of owned paths.
+  pub fn selection_owned(&self) -> Vec<PathBuf> {
+    self
+      .selection()
+      .into_iter()
+      .map(Path::to_path_buf)
+      .collect()
+  }
+
+  /// Name of the named filter that was chosen when the selection was confirmed, e.g. to pick the
+  /// format of a saved file. `None` until the dialog is [`State::Selected`], or if no named filters
+  /// were added.
+  pub fn selected_filter(&self) -> Option<&str> {
+    match self.state {
+      State::Selected => self.active_filter_name(),
+      _ => None,
+    }
+  }
+
+  /// Entry of the resulting file, with its type, size and modification time.
+  pub fn selected_info(&self) -> Option<&FileInfo> {
+    self.selected_file.as_ref()
+  }
+
+  /// Copy the path of the selected file, or in multi-select mode the paths of all selected
+  /// entries on separate lines, to the clipboard.
+  pub fn copy_selection_to_clipboard(&self, ctx: &Context) {
+    let paths = match self.multi_select_enabled {
+      true => self.selection(),
+      false => self.path().into_iter().collect(),
+    };
+    if paths.is_empty() {
+      return;
+    }
+
+    let text: Vec<String> = paths
+      .into_iter()
+      .map(|path| self.display_path(path))
+      .collect();
+    ctx.copy_text(text.join("\n"));
+  }
+
+  /// Select the file or folder at `path`, opening its parent folder first if needed. In save
+  /// dialogs, a file that doesn't exist yet is put into the file name field. Call this after
+  /// [`FileDialog::open`], which resets the selection.
+  pub fn set_selected(&mut self, path: impl Into<PathBuf>) {
+    let path = path.into();
+    self.open_parent(&path);
+    self.wait_for_listing();
+
+    let Ok(files) = &mut self.files else {
+      return;
+    };
+    let Some(idx) = files.iter().position(|info| info.path == path) else {
+      if let Some(name) = path.file_name() {
+        self.filename_edit = name.to_string_lossy().to_string();
+      }
+      return;
+    };
+
+    for (i, file) in files.iter_mut().enumerate() {
+      file.selected = self.multi_select_enabled && i == idx;
+    }
+    let info = files[idx].clone();
+    self.range_start = Some(idx);
+    self.scroll_to_row = Some(idx);
+    self.select(Some(info));
+  }
+
+  /// Select the entries at `paths` in multi select mode, opening the parent folder of the first
+  /// one if needed. Entries that are in another folder are ignored, as are the ones beyond the
+  /// maximum of [`FileDialog::selection_limits`]. Without multi select, only the first path is
+  /// selected like with [`FileDialog::set_selected`].
+  pub fn set_selection(&mut self, paths: impl IntoIterator<Item = PathBuf>) {
+    let mut paths = paths.into_iter();
+    if !self.multi_select_enabled {
+      if let Some(path) = paths.next() {
+        self.set_selected(path);
+      }
+      return;
+    }
+
+    let paths: Vec<PathBuf> = paths.collect();
+    if let Some(path) = paths.first() {
+      self.open_parent(path);
+    }
+    self.wait_for_listing();
+
+    let max = self.selection_limits.1.unwrap_or(usize::MAX);
+    if let Ok(files) = &mut self.files {
+      let mut first = None;
+      let mut count = 0;
+      for (idx, file) in files.iter_mut().enumerate() {
+        file.selected = count < max && paths.contains(&file.path);
+        if file.selected {
+          count += 1;
+          first.get_or_insert(idx);
+          self.range_start = Some(idx);
+        }
+      }
+      self.scroll_to_row = first;
+    }
+  }
+
+  /// Navigate to the folder containing `path` unless it is already open.
+  fn open_parent(&mut self, path: &Path) {
+    if let Some(parent) = path.parent() {
+      if parent != self.path && !parent.as_os_str().is_empty() {
+        self.set_path(parent);
+      }
+    }
+  }
+
+  /// Whether the entries of the current directory are still being read.
+  pub fn is_loading(&self) -> bool {
+    self.loading
+  }
+
+  /// Entries of the current directory as they are listed, or the error from reading it.
+  pub fn current_entries(&self) -> Result<&[FileInfo], &Error> {
+    self.files.as_deref()
+  }
+
+  /// Currently mounted directory that is being shown in the dialog box. It stays the same after
+  /// the dialog is closed, so it can be stored and passed as the initial path next time.
+  pub fn directory(&self) -> &Path {
+    self.path.as_path()
+  }
+
+  /// Set the dialog's current opened path
+  pub fn set_path(&mut self, path: impl Into<PathBuf>) {
+    self.path = path.into();
+    self.search_query.clear();
+    self.refresh();
+  }
+
+  /// Navigate to the folder of `original` and set the file name to "<name> copy.<ext>", or
+  /// "<name> copy 2.<ext>", etc. if that file already exists. Meant for "Save a Copy" actions.
+  pub fn suggest_save_copy(&mut self, original: &Path) {
+    // A bare file name stays in the current folder.
+    if let Some(parent) = original
+      .parent()
+      .filter(|parent| !parent.as_os_str().is_empty())
+    {
+      self.set_path(parent);
+    }
+
+    let stem = original.file_stem().unwrap_or_default().to_string_lossy();
+    let ext = original.extension().map(|ext| ext.to_string_lossy());
+    let mut number = 1;
+    loop {
+      let name = match number {
+        1 => fill(self.labels.copy_name, &[("name", &stem)]),
+        _ => fill(
+          self.labels.numbered_copy_name,
+          &[("name", &stem), ("number", &number)],
+        ),
+      };
+      let name = match &ext {
+        Some(ext) => format!("{name}.{ext}"),
+        None => name,
+      };
+      if !self.file_system.exists(&self.path.join(&name)) {
+        self.filename_edit = name;
+        break;
+      }
+      number += 1;
+    }
+  }
+
+  /// Dialog state.
+  pub fn state(&self) -> State {
+    self.state
+  }
+
+  /// Returns true, if the file selection was confirmed. This is already the case for the
+  /// frame in which the user clicked the Open/Save button.
+  pub fn selected(&self) -> bool {
+    self.state == State::Selected
+  }
+
+  /// Returns the confirmed selection once, and `None` on later calls until the dialog is opened
+  /// and confirmed again. Unlike [`Self::selected`], this only fires in a single frame.
+  pub fn take_confirmed(&mut self) -> Option<Vec<PathBuf>> {
+    if self.state != State::Selected || self.consumed {
+      return None;
+    }
+    self.consumed = true;
+
+    match self.multi_select_enabled {
+      true => Some(self.selection_owned()),
+      false => self.path_owned().map(|path| vec![path]),
+    }
+  }
+
+  fn open_selected(&mut self) {
+    if let Some(info) = &self.selected_file {
+      if info.is_dir() {
+        let path = self.resolve_link(info);
+        // Stay in the current folder rather than navigating into one that can't be listed. A
+        // background read isn't waited for, so that a slow folder doesn't block the frame.
+        if self.check_readable && !self.async_loading {
+          if let Err(err) = self.file_system.check_readable(&path) {
+            self.last_error = Some(self.open_error(&path, &err));
+            return;
+          }
+        }
+        self.last_error = None;
+        let previous = self.path.clone();
+        self.set_path(path);
+        if self.check_readable && self.pending_listing.is_some() {
+          self.back_on_error = Some(previous);
+        }
+      } else if self.dialog_type == DialogType::OpenFile {
+        self.confirm();
+      }
+    } else if self.multi_select_enabled && self.dialog_type == DialogType::OpenFile {
+      match self.confirm_multi_summary {
+        true => self.show_multi_summary = true,
+        false => self.confirm(),
+      }
+    }
+  }
+
+  fn confirm(&mut self) {
+    // Ignore a confirmation after the dialog was cancelled or already confirmed in this frame.
+    if self.state != State::Open {
+      return;
+    }
+    self.confirmed = true;
+
+    if self.follow_symlinks {
+      if let Some(info) = &self.selected_file {
+        if info.is_symlink() {
+          self.selected_file = Some(self.file_system.entry(&self.resolve_link(info)));
+        }
+      }
+      self.link_targets = self
+        .files
+        .iter()
+        .flatten()
+        .filter(|file| file.selected && file.is_symlink())
+        .filter_map(|file| {
+          let target = self.file_system.canonicalize(&file.path).ok()?;
+          Some((file.path.clone(), target))
+        })
+        .collect();
+    }
+  }
+
+  /// Path of the target of `info` if it is a symbolic link that is followed, otherwise its path.
+  fn resolve_link(&self, info: &FileInfo) -> PathBuf {
+    match self.follow_symlinks && info.is_symlink() {
+      true => self
+        .file_system
+        .canonicalize(&info.path)
+        .unwrap_or_else(|_| info.path.clone()),
+      false => info.path.clone(),
+    }
+  }
+
+  fn refresh(&mut self) {
+    self.back_on_error = None;
+    self.path_edit = self.display_path(&self.path);
+    self.path_suggestions.clear();
+    #[cfg(unix)]
+    if self.show_mounts {
+      self.mounts = get_mounts();
+    }
+
+    self.select(None);
+    self.selected_file = None;
+    self.link_targets.clear();
+    // Only the current folder's thumbnails are kept, which also picks up images that changed.
+    #[cfg(feature = "image")]
+    self.thumbnails.clear();
+    self.focused_index = None;
+    self.search_match = None;
+    self.loading = true;
+
+    // A listing is outdated once the directory was modified after it was read. Reading the
+    // modification time could block as long as reading the directory, so not when loading async.
+    let modified = match self.cache_limit == 0 || self.async_loading {
+      true => None,
+      false => self.file_system.entry(&self.path).modified(),
+    };
+    let cached = self.cache.iter().position(|cached| {
+      cached.path == self.path && modified.is_some_and(|modified| modified <= cached.read_time)
+    });
+    if let Some(pos) = cached {
+      // Move the listing to the front, as it is the most recently used.
+      let cached = self.cache.remove(pos).unwrap();
+      let entries = cached.entries.clone();
+      self.cache.push_front(cached);
+      self.pending_listing = None;
+      self.set_listing(Ok(entries));
+    } else if self.async_loading {
+      // Replacing the receiver drops the one of a read that is still in flight, so its result is
+      // ignored.
+      let (sender, receiver) = mpsc::channel();
+      let file_system = Arc::clone(&self.file_system);
+      let path = self.path.clone();
+      let lazy_metadata = self.lazy_metadata;
+      thread::spawn(move || {
+        let _ = sender.send(file_system.read_dir(&path, lazy_metadata));
+      });
+
+      self.files = Ok(Vec::new());
+      self.pending_listing = Some(receiver);
+      self.apply_search();
+    } else {
+      self.pending_listing = None;
+      let entries = self.file_system.read_dir(&self.path, self.lazy_metadata);
+      self.cache_listing(&entries);
+      self.set_listing(entries);
+    }
+  }
+
+  /// Watch the current directory and refresh the listing once changes have settled down.
+  #[cfg(feature = "watch")]
+  fn poll_watch(&mut self, ctx: &Context) {
+    use notify::Watcher;
+
+    if !self.watch {
+      return;
+    }
+
+    if self
+      .watcher
+      .as_ref()
+      .map_or(true, |watch| watch.path != self.path)
+    {
+      let (sender, receiver) = mpsc::channel();
+      let repaint = ctx.clone();
+      let watcher = notify::recommended_watcher(move |event| {
+        if sender.send(event).is_ok() {
+          repaint.request_repaint();
+        }
+      })
+      .and_then(|mut watcher| {
+        watcher.watch(&self.path, notify::RecursiveMode::NonRecursive)?;
+        Ok(watcher)
+      });
+      self.watcher = Some(Watch {
+        path: self.path.clone(),
+        _watcher: watcher.ok(),
+        receiver,
+        changed_at: None,
+      });
+    }
+
+    let Some(watch) = &mut self.watcher else {
+      return;
+    };
+    let now = ctx.input(|state| state.time);
+    while let Ok(event) = watch.receiver.try_recv() {
+      // Reading the directory or previewing a file are accesses.
+      if event.is_ok_and(|event| !event.kind.is_access()) {
+        watch.changed_at = Some(now);
+      }
+    }
+
+    let Some(changed_at) = watch.changed_at else {
+      return;
+    };
+    if now - changed_at < WATCH_DEBOUNCE {
+      ctx.request_repaint_after(std::time::Duration::from_secs_f64(
+        WATCH_DEBOUNCE - (now - changed_at),
+      ));
+      return;
+    }
+
+    watch.changed_at = None;
+    self.keep_selection = match self.multi_select_enabled {
+      true => self
+        .selection()
+        .into_iter()
+        .map(Path::to_path_buf)
+        .collect(),
+      false => self
+        .selected_file
+        .iter()
+        .map(|info| info.path.clone())
+        .collect(),
+    };
+    self.invalidate_cache(&self.path.clone());
+    self.refresh();
+  }
+
+  /// Keep the `entries` read from the current path in the cache.
+  fn cache_listing(&mut self, entries: &Result<Vec<FileInfo>, Error>) {
+    let Ok(entries) = entries else {
+      return;
+    };
+    if self.cache_limit == 0 {
+      return;
+    }
+
+    self.cache.retain(|cached| cached.path != self.path);
+    self.cache.push_front(CachedListing {
+      path: self.path.clone(),
+      read_time: SystemTime::now(),
+      entries: entries.clone(),
+    });
+    self.cache.truncate(self.cache_limit);
+  }
+
+  /// Remove the listing of the directory at `path` from the cache, so it is read again.
+  fn invalidate_cache(&mut self, path: &Path) {
+    self.cache.retain(|cached| cached.path != path);
+    self.tree.remove(path);
+  }
+
+  /// Show or hide hidden files, in the folder tree as well.
+  fn set_show_hidden(&mut self, show_hidden: bool) {
+    self.show_hidden = show_hidden;
+    self.tree.clear();
+    self.refresh();
+  }
+
+  /// Take the entries of a background read once they have arrived.
+  fn poll_listing(&mut self) {
+    let Some(receiver) = &self.pending_listing else {
+      return;
+    };
+    let entries = match receiver.try_recv() {
+      Ok(entries) => entries,
+      Err(mpsc::TryRecvError::Empty) => return,
+      Err(mpsc::TryRecvError::Disconnected) => Err(Error::other("reading the folder failed")),
+    };
+    self.pending_listing = None;
+    self.cache_listing(&entries);
+    self.set_listing(entries);
+  }
+
+  /// Block until a background read has finished, for calls that need the entries right away.
+  fn wait_for_listing(&mut self) {
+    if let Some(receiver) = self.pending_listing.take() {
+      let entries = receiver
+        .recv()
+        .unwrap_or_else(|_| Err(Error::other("reading the folder failed")));
+      self.cache_listing(&entries);
+      self.set_listing(entries);
+    }
+  }
+
+  /// Show the `entries` read from the current path.
+  fn set_listing(&mut self, entries: Result<Vec<FileInfo>, Error>) {
+    if let (Some(previous), Err(err)) = (self.back_on_error.take(), &entries) {
+      self.last_error = Some(self.open_error(&self.path, err));
+      self.set_path(previous);
+      return;
+    }
+
+    self.files = self.read_folder(entries);
+    self.loading = false;
+    if self.files.is_ok() {
+      self.recent.retain(|path| *path != self.path);
+      self.recent.push_front(self.path.clone());
+      self.recent.truncate(self.recent_limit);
+    }
+    self.apply_search();
+
+    // Select the entries that were selected before an automatic refresh, if they still exist.
+    let keep_selection = std::mem::take(&mut self.keep_selection);
+    if let Ok(files) = &mut self.files {
+      let mut kept = None;
+      for file in files
+        .iter_mut()
+        .filter(|file| keep_selection.contains(&file.path))
+      {
+        file.selected = self.multi_select_enabled;
+        kept.get_or_insert(file.clone());
+      }
+      if let Some(info) = kept {
+        self.select(Some(info));
+      }
+    }
+
+    if let Some(idx) = self.initial_highlight.take() {
+      self.highlight(idx);
+    }
+  }
+
+  /// Switch to `view_mode`, keeping the settings of the grid view from the builder.
+  fn set_view_mode(&mut self, view_mode: ViewMode) {
+    self.view_mode = match view_mode {
+      ViewMode::Grid { .. } => ViewMode::Grid {
+        columns: self.grid_columns,
+      },
+      view_mode => view_mode,
+    };
+  }
+
+  /// Context menu of an entry with actions depending on the dialog type and the entry.
+  fn context_menu(&self, response: &egui::Response, info: &FileInfo) -> Option<Command> {
+    let mut command = None;
+    response.context_menu(|ui| {
+      let open = self.activate_command(info);
+      let open_text = match (info.is_dir(), self.dialog_type) {
+        (false, DialogType::SaveFile) => self.labels.save_button,
+        _ => self.labels.open_button,
+      };
+      if ui
+        .add_enabled(open.is_some(), egui::Button::new(open_text))
+        .clicked()
+      {
+        command = open;
+        ui.close_menu();
+      }
+
+      if self.rename && ui.button(self.labels.rename_button).clicked() {
+        command = Some(Command::StartRename(info.clone()));
+        ui.close_menu();
+      }
+
+      if self.delete && ui.button(self.labels.delete_button).clicked() {
+        // An entry of a multi selection deletes all of it.
+        command = Some(match info.selected && self.selected_count() > 1 {
+          true => Command::DeleteSelection,
+          false => Command::Delete(info.path.clone()),
+        });
+        ui.close_menu();
+      }
+
+      ui.separator();
+      #[cfg(feature = "open")]
+      if info.is_file() && ui.button(self.labels.open_with_default_app).clicked() {
+        command = Some(Command::OpenWithDefaultApp(info.path.clone()));
+        ui.close_menu();
+      }
+
+      #[cfg(feature = "open")]
+      if ui.button(self.labels.show_in_file_manager).clicked() {
+        command = Some(Command::ShowInFileManager(info.path.clone()));
+        ui.close_menu();
+      }
+
+      if ui.button(self.labels.copy_path).clicked() {
+        match self.multi_select_enabled && info.selected {
+          true => self.copy_selection_to_clipboard(ui.ctx()),
+          false => ui.ctx().copy_text(self.display_path(&info.path)),
+        }
+        ui.close_menu();
+      }
+    });
+    command
+  }
+
+  /// Sort the listed files again, e.g. after the sort order changed, and scroll to the selection.
+  fn sort_files(&mut self) {
+    let Ok(files) = &mut self.files else {
+      return;
+    };
+
+    let path_at = |idx: Option<usize>| {
+      idx
+        .and_then(|idx| files.get(idx))
+        .map(|info| info.path.clone())
+    };
+    let anchor = path_at(self.range_start);
+    let focused = path_at(self.focused_index);
+
+    let (sort_by, reverse) = (self.sort_by, self.sort_reverse);
+    files.sort_by(|a, b| compare_files(a, b, sort_by, reverse));
+
+    // Indices into the list are no longer valid.
+    let index_of =
+      |path: Option<PathBuf>| path.and_then(|path| files.iter().position(|info| info.path == path));
+    self.range_start = index_of(anchor);
+    self.focused_index = index_of(focused);
+    self.search_match = None;
+
+    let selected = match &self.selected_file {
+      Some(selected) => files.iter().position(|info| info.path == selected.path),
+      None => self.range_start,
+    };
+    self.scroll_to_row = selected;
+    self.apply_search();
+  }
+
+  /// Update the shown entries to those whose name contains the search query, ignoring case, and
+  /// files that pass the active named filter. Hidden entries are deselected, so actions on the
+  /// selection only affect what the user can see.
+  fn apply_search(&mut self) {
+    let files = self.files.as_deref_mut().unwrap_or_default();
+    let query = self.search_query.to_lowercase();
+    let filter = self
+      .filters
+      .get(self.active_filter)
+      .map(|(_, filter, _)| filter);
+    self.visible = files
+      .iter_mut()
+      .enumerate()
+      .filter_map(|(idx, info)| {
+        let shown = match filter {
+          Some(filter) if !info.is_dir() && !filter(&info.path) => false,
+          _ => query.is_empty() || get_file_name(info).to_lowercase().contains(&query),
+        };
+        if !shown {
+          info.selected = false;
+        }
+        shown.then_some(idx)
+      })
+      .collect();
+  }
+
+  /// Select the next entry whose name contains the search query, wrapping around at the end.
+  fn find_next(&mut self, reverse: bool) {
+    let Ok(files) = &mut self.files else {
+      return;
+    };
+
+    let query = self.search_query.to_lowercase();
+    let len = files.len();
+    if query.is_empty() || len == 0 {
+      return;
+    }
+
+    let start = self
+      .search_match
+      .unwrap_or(if reverse { 0 } else { len - 1 });
+    let found = (1..=len)
+      .map(|step| match reverse {
+        true => (start + len - step) % len,
+        false => (start + step) % len,
+      })
+      .find(|&idx| get_file_name(&files[idx]).to_lowercase().contains(&query));
+
+    let Some(idx) = found else {
+      return;
+    };
+
+    if self.multi_select_enabled {
+      for (n, file) in files.iter_mut().enumerate() {
+        file.selected = n == idx;
+      }
+      self.range_start = Some(idx);
+    } else {
+      let info = files[idx].clone();
+      self.select(Some(info));
+    }
+
+    self.search_match = Some(idx);
+    self.focused_index = Some(idx);
+    self.scroll_to_row = Some(idx);
+  }
+
+  /// Highlight the entry at `idx`, ignoring indices outside of the listing. In multi-select mode
+  /// this sets the anchor of range selections.
+  fn highlight(&mut self, idx: usize) {
+    let info = match &self.files {
+      Ok(files) if idx < files.len() => files[idx].clone(),
+      _ => return,
+    };
+
+    if self.multi_select_enabled {
+      self.range_start = Some(idx);
+    } else {
+      self.select(Some(info));
+    }
+    self.focused_index = Some(idx);
+  }
+
+  /// Select the entry at `path` after it was created or renamed, and scroll to it.
+  fn reveal(&mut self, path: PathBuf) {
+    self.wait_for_listing();
+    let idx = self
+      .files
+      .iter()
+      .flatten()
+      .position(|info| info.path == path);
+    self.focused_index = idx;
+    self.scroll_to_row = idx;
+    self.select(Some(self.file_system.entry(&path)));
+  }
+
+  /// Move the keyboard focus to the shown entry at `position`, selecting it. In multi-select mode
+  /// `extend` selects the range from the anchor instead.
+  fn focus(&mut self, position: usize, extend: bool) {
+    let Some(&idx) = self.visible.get(position) else {
+      return;
+    };
+
+    if self.multi_select_enabled {
+      match extend && self.range_start.is_some() {
+        true => self.select_range(idx),
+        false => {
+          if let Ok(files) = &mut self.files {
+            for (n, file) in files.iter_mut().enumerate() {
+              file.selected = n == idx;
+            }
+          }
+          self.range_start = Some(idx);
+        }
+      }
+    } else if let Ok(files) = &self.files {
+      let info = files[idx].clone();
+      self.select(Some(info));
+    }
+
+    self.focused_index = Some(idx);
+    self.scroll_to_row = Some(idx);
+  }
+
+  fn select(&mut self, file: Option<FileInfo>) {
+    if let Some(info) = &file {
+      if !info.is_dir() {
+        get_file_name(info).clone_into(&mut self.filename_edit);
+      }
+    }
+    self.selected_file = file;
+  }
+
+  fn select_reset_multi(&mut self, idx: usize) {
+    if let Ok(files) = &mut self.files {
+      let selected_val = files[idx].selected;
+      for file in files.iter_mut() {
+        file.selected = false;
+      }
+      files[idx].selected = !selected_val;
+      self.range_start = Some(idx);
+    }
+  }
+
+  fn select_switch_multi(&mut self, idx: usize) {
+    let full = self.selection_full();
+    if let Ok(files) = &mut self.files {
+      if full && !files[idx].selected {
+        return;
+      }
+      files[idx].selected = !files[idx].selected;
+      if files[idx].selected {
+        self.range_start = Some(idx);
+      } else {
+        self.range_start = None;
+      }
+    } else {
+      self.range_start = None;
+    }
+  }
+
+  fn select_range(&mut self, idx: usize) {
+    let mut count = self.selected_count();
+    if let Ok(files) = &mut self.files {
+      if let Some(range_start) = self.range_start {
+        // Only select the entries that are shown.
+        let start = self.visible.iter().position(|&i| i == range_start);
+        let end = self.visible.iter().position(|&i| i == idx);
+        if let (Some(start), Some(end)) = (start, end) {
+          let range = cmp::min(start, end)..=cmp::max(start, end);
+          for &i in &self.visible[range] {
+            if files[i].selected {
+              continue;
+            }
+            if self.selection_limits.1.is_some_and(|max| count >= max) {
+              break;
+            }
+            files[i].selected = true;
+            count += 1;
+          }
+        }
+      }
+    }
+  }
+
+  /// Select the shown files that pass the file name filter, up to the maximum selection size.
+  fn select_all(&mut self) {
+    let mut count = self.selected_count();
+    if let Ok(files) = &mut self.files {
+      for &idx in &self.visible {
+        let info = &mut files[idx];
+        if info.selected || info.is_dir() || !(self.filename_filter)(get_file_name(info)) {
+          continue;
+        }
+        if self.selection_limits.1.is_some_and(|max| count >= max) {
+          break;
+        }
+        info.selected = true;
+        count += 1;
+      }
+    }
+  }
+
+  /// Number of shown entries and, in multi select mode, the size of the selection.
+  fn status_text(&self) -> String {
+    let count = self.visible.len();
+    let mut text = match count {
+      1 => self.labels.one_item.to_string(),
+      count => fill(self.labels.items, &[("count", &count)]),
+    };
+    if self.multi_select_enabled {
+      let selection: Vec<&FileInfo> = self
+        .files
+        .iter()
+        .flatten()
+        .filter(|info| info.selected)
+        .collect();
+      let selected = fill(self.labels.selected_count, &[("count", &selection.len())]);
+      let _ = write!(text, ", {selected}");
+      if selection.iter().any(|info| info.is_file()) {
+        let size: u64 = selection.iter().map(|info| info.size()).sum();
+        let _ = write!(text, ", {}", format_size(size));
+      }
+    }
+    text
+  }
+
+  /// Number of selected entries in multi select mode.
+  fn selected_count(&self) -> usize {
+    self
+      .files
+      .iter()
+      .flatten()
+      .filter(|info| info.selected)
+      .count()
+  }
+
+  /// Whether the multi selection has reached its maximum size.
+  fn selection_full(&self) -> bool {
+    let (_, max) = self.selection_limits;
+    max.is_some_and(|max| self.selected_count() >= max)
+  }
+
+  /// The file name as it is used, without surrounding whitespace if trimming is enabled.
+  fn filename(&self) -> &str {
+    match self.trim_filename {
+      true => self.filename_edit.trim(),
+      false => &self.filename_edit,
+    }
+  }
+
+  /// Warning about the typed file name not being used exactly as typed, shown below the field.
+  fn filename_warning(&self) -> Option<&'static str> {
+    if self.filename() != self.filename_edit {
+      return Some(self.labels.trimmed_warning);
+    }
+
+    #[cfg(windows)]
+    if self.filename().ends_with('.') {
+      return Some(self.labels.trailing_dot_warning);
+    }
+
+    None
+  }
+
+  /// The first path like "name (1).ext", "name (2).ext", etc. next to `path` that doesn't exist.
+  fn unique_path(&self, path: &Path) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let ext = path.extension().map(|ext| ext.to_string_lossy());
+    let mut number = 1;
+    loop {
+      let name = match &ext {
+        Some(ext) => format!("{stem} ({number}).{ext}"),
+        None => format!("{stem} ({number})"),
+      };
+      let unique = path.with_file_name(name);
+      if !self.file_system.exists(&unique) {
+        return unique;
+      }
+      number += 1;
+    }
+  }
+
+  /// Path to save to, with the default extension appended if the file name has none.
+  fn save_path(&self) -> PathBuf {
+    let path = self.path.join(self.filename());
+    match &self.default_extension {
+      Some(extension) if path.extension().is_none() => {
+        let mut name = path.clone().into_os_string();
+        name.push(".");
+        name.push(extension);
+        PathBuf::from(name)
+      }
+      _ => path,
+    }
+  }
+
+  fn can_save(&self) -> bool {
+    !self.filename().is_empty() && (self.filename_filter)(self.filename())
+  }
+
+  /// Why files can't be saved in the current folder, checked once after navigating.
+  fn update_write_error(&mut self) {
+    if !self.check_writable || self.dialog_type != DialogType::SaveFile || self.loading {
+      return;
+    }
+    if self
+      .write_error
+      .as_ref()
+      .is_some_and(|(path, _)| *path == self.path)
+    {
+      return;
+    }
+
+    let error = match self.file_system.check_writable(&self.path) {
+      Ok(()) => None,
+      Err(err) => Some(fill(self.labels.write_error, &[("error", &err)])),
+    };
+    self.write_error = Some((self.path.clone(), error));
+  }
+
+  /// Why files can't be saved in the current folder, if it has been checked.
+  fn write_error(&self) -> Option<&str> {
+    match &self.write_error {
+      Some((path, error)) if *path == self.path => error.as_deref(),
+      _ => None,
+    }
+  }
+
+  /// Why the file at `path` can't be saved: the folder isn't writable or the existing file is
+  /// read-only.
+  fn save_error(&self, path: &Path) -> Option<String> {
+    if let Some(error) = self.write_error() {
+      return Some(error.to_string());
+    }
+
+    let existing = self.files.iter().flatten().find(|info| info.path == path);
+    match existing.is_some_and(|info| !info.is_dir() && info.is_read_only()) {
+      true => {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        Some(fill(self.labels.read_only_error, &[("name", &name)]))
+      }
+      false => None,
+    }
+  }
+
+  fn can_open(&self) -> bool {
+    if self.multi_select_enabled {
+      let (min, _) = self.selection_limits;
+      if self.selected_count() < min {
+        return false;
+      }
+      if let Ok(files) = &self.files {
+        for file in files {
+          if file.selected && (self.filename_filter)(get_file_name(file)) {
             return true;
           }
         }
       }
-      false
+      false
+    } else {
+      !self.filename().is_empty() && (self.filename_filter)(self.filename())
+    }
+  }
+
+  fn can_rename(&self) -> bool {
+    if !self.filename().is_empty() {
+      if let Some(file) = &self.selected_file {
+        return get_file_name(file) != self.filename()
+          && self.name_error(self.filename()).is_none();
+      }
+    }
+    false
+  }
+
+  /// Shows the dialog if it is open. It is also responsible for state management.
+  /// Should be called every ui update. A selection confirmed by the user during this call is
+  /// reported by the returned reference right away.
+  pub fn show(&mut self, ctx: &Context) -> &Self {
+    self.update(ctx, |this, is_open| this.ui(ctx, is_open))
+  }
+
+  /// Shows the dialog in `ui` instead of a window, e.g. in a panel of the app. It is also
+  /// responsible for state management like [`FileDialog::show`], and should be called every ui
+  /// update.
+  pub fn show_inside(&mut self, ui: &mut Ui) -> &Self {
+    let ctx = ui.ctx().clone();
+    self.update(&ctx, |this, _is_open| {
+      ui.scope(|ui| {
+        if this.prompt.is_some() {
+          ui.disable();
+        }
+        this.window_size = ui.max_rect().size();
+        this.ui_in_window(ui);
+      });
+
+      if this.prompt.is_some() {
+        this.prompt_ui(&ctx);
+      }
+    })
+  }
+
+  /// Update the state around showing the dialog with `add_contents`, which may close it by
+  /// setting its argument to false.
+  fn update(&mut self, ctx: &Context, add_contents: impl FnOnce(&mut Self, &mut bool)) -> &Self {
+    self.state = match self.state {
+      State::Open => {
+        if ctx.input(|state| state.key_pressed(Key::Escape)) {
+          // Escape dismisses the prompt or reverts an inline rename first.
+          if self.prompt.take().is_none() && self.inline_rename.take().is_none() {
+            self.cancel();
+          }
+        }
+
+        let mut is_open = true;
+        add_contents(self, &mut is_open);
+        let confirmed = std::mem::take(&mut self.confirmed);
+        match is_open {
+          true if confirmed => State::Selected,
+          true => self.state,
+          false => {
+            self.cancel();
+            self.state
+          }
+        }
+      }
+      _ => {
+        #[cfg(feature = "watch")]
+        {
+          self.watcher = None;
+        }
+        State::Closed
+      }
+    };
+
+    self
+  }
+
+  fn ui(&mut self, ctx: &Context, is_open: &mut bool) {
+    // Like the window's own id, which is derived from its title unless it is set.
+    let title = self.window_title();
+    let id = self.id.unwrap_or_else(|| Id::new(&title));
+    let mut window = Window::new(RichText::new(title).strong())
+      .id(id)
+      .open(is_open)
+      .default_size(self.default_size)
+      .resizable(self.resizable)
+      .collapsible(false);
+
+    if let Some((align, offset)) = self.anchor {
+      window = window.anchor(align, offset);
+    }
+
+    if let Some(current_pos) = self.current_pos {
+      window = window.current_pos(current_pos);
+    }
+
+    if let Some(default_pos) = self.default_pos {
+      window = window.default_pos(default_pos);
+    }
+
+    if self.modal {
+      // Scrim over the other windows that takes the clicks meant for the UI below it. The dialog
+      // window is moved on top of it, popups are in front of both.
+      let screen = ctx.screen_rect();
+      let scrim = egui::Area::new(id.with("egui_file_scrim"))
+        .order(egui::Order::Middle)
+        .fixed_pos(screen.min)
+        .show(ctx, |ui| {
+          ui.painter()
+            .rect_filled(screen, 0.0, egui::Color32::from_black_alpha(96));
+          ui.allocate_response(screen.size(), egui::Sense::click_and_drag());
+        });
+      ctx.move_to_top(scrim.response.layer_id);
+    }
+
+    window.show(ctx, |ui| {
+      if self.keep_on_top || self.modal {
+        ui.ctx().move_to_top(ui.layer_id());
+      }
+      if self.prompt.is_some() {
+        ui.disable();
+      }
+      self.window_size = ui.max_rect().size();
+      self.ui_in_window(ui)
+    });
+
+    if self.prompt.is_some() {
+      self.prompt_ui(ctx);
+    }
+  }
+
+  /// Cancel the dialog, or ask first if a typed file name would be discarded.
+  fn cancel(&mut self) {
+    let typed = self.dialog_type == DialogType::SaveFile && !self.filename_edit.is_empty();
+    match self.confirm_cancel && typed {
+      true => self.prompt = Some(Prompt::Discard),
+      false => self.state = State::Cancelled,
+    }
+  }
+
+  /// Window with the pending prompt, answering yes carries out its action.
+  fn prompt_ui(&mut self, ctx: &Context) {
+    let Some(prompt) = &self.prompt else {
+      return;
+    };
+
+    let name = |path: &PathBuf| {
+      path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string()
+    };
+    let (title, path, text) = match prompt {
+      Prompt::Overwrite(path) => {
+        let text = fill(self.labels.overwrite_text, &[("name", &name(path))]);
+        (self.labels.overwrite_title, Some(path), text)
+      }
+      Prompt::Delete(path) => {
+        let template = match self.trashes() {
+          true => self.labels.trash_text,
+          false => self.labels.delete_text,
+        };
+        let text = fill(template, &[("name", &name(path))]);
+        (self.labels.delete_title, Some(path), text)
+      }
+      Prompt::DeleteSelection(paths) => {
+        let template = match self.trashes() {
+          true => self.labels.trash_selection_text,
+          false => self.labels.delete_selection_text,
+        };
+        let text = fill(template, &[("count", &paths.len())]);
+        (self.labels.delete_title, None, text)
+      }
+      Prompt::DeleteFolder(path, items) => {
+        let template = match self.trashes() {
+          true => self.labels.trash_folder_text,
+          false => self.labels.delete_folder_text,
+        };
+        let text = fill(template, &[("name", &name(path)), ("count", items)]);
+        (self.labels.delete_title, Some(path), text)
+      }
+      Prompt::Discard => {
+        let text = fill(self.labels.discard_text, &[("name", &self.filename_edit)]);
+        (self.labels.discard_title, None, text)
+      }
+      Prompt::NewFolder(_) => return self.new_folder_ui(ctx),
+    };
+
+    let mut answer = None;
+    Window::new(title)
+      .id(Id::new(("egui_file_prompt", path)))
+      .anchor(Align2::CENTER_CENTER, Vec2::ZERO)
+      .collapsible(false)
+      .resizable(false)
+      .show(ctx, |ui| {
+        ui.ctx().move_to_top(ui.layer_id());
+        ui.label(text);
+        ui.add_space(ui.spacing().item_spacing.y);
+        ui.horizontal(|ui| {
+          if ui.button(self.labels.yes_button).clicked() {
+            answer = Some(true);
+          }
+          if ui.button(self.labels.no_button).clicked() {
+            answer = Some(false);
+          }
+        });
+      });
+
+    if let Some(answer) = answer {
+      self.apply_command(Command::AnswerPrompt(answer));
+    }
+  }
+
+  /// Window that asks for the name of a new folder, which is created in the current folder.
+  fn new_folder_ui(&mut self, ctx: &Context) {
+    let Some(Prompt::NewFolder(name)) = &mut self.prompt else {
+      return;
+    };
+    let mut name = std::mem::take(name);
+
+    let mut answer = None;
+    Window::new(self.labels.new_folder_button)
+      .id(Id::new("egui_file_new_folder"))
+      .anchor(Align2::CENTER_CENTER, Vec2::ZERO)
+      .collapsible(false)
+      .resizable(false)
+      .show(ctx, |ui| {
+        ui.ctx().move_to_top(ui.layer_id());
+        let response = ui.text_edit_singleline(&mut name);
+        if !response.has_focus() && !response.lost_focus() {
+          response.request_focus();
+          select_file_stem(ui.ctx(), response.id, &name);
+        }
+        let enter_pressed =
+          response.lost_focus() && ui.input(|state| state.key_pressed(Key::Enter));
+
+        let error = self.name_error(&name);
+        if let Some(error) = error {
+          ui.colored_label(ui.visuals().error_fg_color, error);
+          if enter_pressed {
+            // Keep editing the name.
+            response.request_focus();
+          }
+        }
+        ui.add_space(ui.spacing().item_spacing.y);
+        ui.horizontal(|ui| {
+          let create = egui::Button::new(self.labels.create_button);
+          if ui.add_enabled(error.is_none(), create).clicked() || enter_pressed && error.is_none() {
+            answer = Some(true);
+          }
+          if ui.button(self.labels.cancel_button).clicked() {
+            answer = Some(false);
+          }
+        });
+      });
+
+    self.prompt = Some(Prompt::NewFolder(name));
+    if let Some(answer) = answer {
+      self.apply_command(Command::AnswerPrompt(answer));
+    }
+  }
+
+  /// Why `name` can't be used for a new or renamed entry in the current folder, if it can't.
+  fn name_error(&self, name: &str) -> Option<&'static str> {
+    let illegal: &[char] = match cfg!(windows) {
+      true => &['<', '>', ':', '"', '/', '\\', '|', '?', '*'],
+      false => &['/'],
+    };
+    if name.trim().is_empty() {
+      Some(self.labels.empty_name_error)
+    } else if name == "." || name == ".." {
+      Some(self.labels.reserved_name_error)
+    } else if name.contains(illegal) || name.contains(char::is_control) {
+      Some(self.labels.illegal_name_error)
+    } else if cfg!(windows) && name.ends_with(['.', ' ']) {
+      Some(self.labels.trailing_name_error)
+    } else if self.file_system.exists(&self.path.join(name)) {
+      Some(self.labels.existing_name_error)
+    } else {
+      None
+    }
+  }
+
+  /// Delete the entry at `path` according to the delete mode, without refreshing.
+  fn remove_entry(&self, path: &Path, recursive: bool) -> Result<(), Error> {
+    #[cfg(feature = "trash")]
+    if self.delete_mode == DeleteMode::Trash {
+      return self.file_system.trash(path);
+    }
+
+    match recursive {
+      true => self.file_system.remove_all(path),
+      false => self.file_system.remove(path),
+    }
+  }
+
+  /// Delete the entries at `paths`, continuing after errors, and clear the selection.
+  fn delete_all(&mut self, paths: Vec<PathBuf>) {
+    let mut deleted = 0;
+    let mut failed = Vec::new();
+    for path in paths {
+      match self.remove_entry(&path, self.delete_recursive) {
+        Ok(_) => {
+          deleted += 1;
+          self.emit_fs_event(FsEvent::Deleted(path));
+        }
+        Err(error) => {
+          let name = path.file_name().unwrap_or_default().to_string_lossy();
+          failed.push(format!("{name}: {error}"));
+          self.emit_fs_event(FsEvent::Error {
+            op: FsOp::Delete,
+            error,
+          });
+        }
+      }
+    }
+
+    if deleted > 0 {
+      let template = match self.trashes() {
+        true => self.labels.trashed_items,
+        false => self.labels.deleted_items,
+      };
+      self.notify(fill(template, &[("count", &deleted)]), Level::Info);
+    }
+    if !failed.is_empty() {
+      let error = failed.join(", ");
+      self.last_error = Some(fill(self.labels.delete_error, &[("error", &error)]));
+    }
+    self.clear_selection();
+    self.refresh();
+  }
+
+  /// Whether deleted files are moved to the trash.
+  fn trashes(&self) -> bool {
+    cfg!(feature = "trash") && self.delete_mode == DeleteMode::Trash
+  }
+
+  /// Delete a file or an empty folder, or move it to the trash. With `recursive`, folders are
+  /// deleted with everything in them.
+  fn delete(&mut self, path: PathBuf, recursive: bool) {
+    match self.remove_entry(&path, recursive) {
+      Ok(_) => {
+        let text = match self.trashes() {
+          true => self.labels.trashed,
+          false => self.labels.deleted,
+        };
+        self.notify(text, Level::Info);
+        self.emit_fs_event(FsEvent::Deleted(path));
+        self.refresh();
+      }
+      Err(err) => self.fs_error(FsOp::Delete, self.labels.delete_error, err),
+    }
+  }
+
+  /// Rows of the file list in `range`, scrolling to the row at `scroll_to_row` if it is given. The
+  /// row of `inline_rename` shows a text field instead, Enter renames the entry.
+  fn file_list_ui(
+    &self,
+    ui: &mut Ui,
+    range: Range<usize>,
+    scroll_to_row: Option<usize>,
+    inline_rename: &mut Option<InlineRename>,
+  ) -> Option<Command> {
+    let mut command = None;
+    let files = match self.files.as_ref() {
+      Ok(files) => files,
+      Err(err) => return self.listing_error_ui(ui, err),
+    };
+
+    ui.with_layout(ui.layout().with_cross_justify(true), |ui| {
+      let selected = self.selected_file.as_ref().map(|info| &info.path);
+      let now = SystemTime::now();
+
+      // Names are cut off before the columns.
+      let columns_width: f32 = self.columns().iter().map(|column| column.width()).sum();
+      let name_width = match columns_width > 0.0 {
+        true => Some(ui.available_width() - columns_width - 2.0 * ui.spacing().button_padding.x),
+        false => None,
+      };
+
+      let parent = self.parent_entries();
+      for slot in range {
+        if slot < parent {
+          let response = ui.selectable_label(false, self.entry_label(self.icons.folder, ".."));
+          // Only on double-click, as the first click would already go up and the second one would
+          // land on the new ".." row.
+          if response.double_clicked() {
+            command = Some(Command::UpDirectory);
+          }
+          continue;
+        }
+
+        let idx = self.visible[slot - parent];
+        let info = &files[idx];
+        let label = self.entry_label(self.icons.entry(info), &self.entry_name(info));
+
+        if let Some(rename) = inline_rename
+          .as_mut()
+          .filter(|rename| rename.path == info.path)
+        {
+          let response = ui.add(
+            TextEdit::singleline(&mut rename.name)
+              .margin(egui::Margin::ZERO)
+              .desired_width(f32::INFINITY),
+          );
+          if std::mem::take(&mut rename.focus) {
+            response.request_focus();
+            select_file_stem(ui.ctx(), response.id, &rename.name);
+          }
+
+          // The name is checked like the one of a new folder, e.g. "../x" would move the entry.
+          let name = match self.trim_filename {
+            true => rename.name.trim(),
+            false => &rename.name,
+          };
+          let unchanged = name == get_file_name(info);
+          let error = self.name_error(name).filter(|_| !unchanged);
+          if let Some(error) = error {
+            response.show_tooltip_text(RichText::new(error).color(ui.visuals().error_fg_color));
+          }
+
+          if response.lost_focus() {
+            let enter_pressed = ui.input(|state| state.key_pressed(Key::Enter));
+            if enter_pressed && error.is_some() {
+              // Keep editing the name.
+              response.request_focus();
+              continue;
+            }
+            if enter_pressed && !unchanged {
+              command = Some(Command::Rename(
+                info.path.clone(),
+                info.path.with_file_name(name),
+              ));
+            }
+            *inline_rename = None;
+          }
+          continue;
+        }
+
+        let is_selected = if self.multi_select_enabled {
+          files[idx].selected
+        } else {
+          Some(&info.path) == selected
+        };
+        let response = match &self.row_renderer {
+          Some(renderer) => {
+            let view = FileInfoView {
+              info,
+              label: &label,
+              selected: is_selected,
+            };
+            renderer(ui, &view)
+          }
+          None => {
+            let text = match name_width {
+              Some(width) => truncated_text(ui, label, width),
+              None => label.into(),
+            };
+            ui.selectable_label(is_selected, text).on_hover_ui(|ui| {
+              ui.label(self.hover_text(info));
+            })
+          }
+        };
+        self.paint_focus(ui, response.rect, idx);
+        self.paint_columns(ui, response.rect, info, now);
+        if scroll_to_row == Some(idx) {
+          response.scroll_to_me(Some(egui::Align::Center));
+        }
+
+        if let Some(cmd) = self.entry_command(ui, &response, idx, info) {
+          command = Some(cmd);
+        }
+      }
+    });
+
+    command
+  }
+
+  /// Message in place of the entries when the folder can't be read, with a way back if that is for
+  /// lack of permissions.
+  fn listing_error_ui(&self, ui: &mut Ui, err: &Error) -> Option<Command> {
+    if err.kind() != std::io::ErrorKind::PermissionDenied {
+      ui.label(err.to_string());
+      return None;
+    }
+
+    let mut command = None;
+    ui.vertical_centered(|ui| {
+      ui.add_space(ui.spacing().interact_size.y);
+      ui.label(self.labels.permission_denied);
+      if ui.button(self.labels.go_back_button).clicked() {
+        command = Some(Command::GoBack);
+      }
+    });
+    command
+  }
+
+  /// Tiles of the grid view for the rows in `range`, scrolling to the entry at `scroll_to`.
+  fn file_grid_ui(
+    &mut self,
+    ui: &mut Ui,
+    range: Range<usize>,
+    columns: usize,
+    scroll_to: Option<usize>,
+  ) -> Option<Command> {
+    let mut command = None;
+    let files = match self.files.as_ref() {
+      Ok(files) => files,
+      Err(err) => return self.listing_error_ui(ui, err),
+    };
+
+    let spacing = ui.spacing().item_spacing;
+    let width = (ui.available_width() - spacing.x * (columns - 1) as f32) / columns as f32;
+    let tile_size = egui::vec2(width, self.tile_height(ui));
+    let selected = self.selected_file.as_ref().map(|info| &info.path);
+
+    #[cfg(feature = "image")]
+    let mut decode_budget = THUMBNAILS_PER_FRAME;
+
+    // Taken out while the tiles borrow the rest of the dialog.
+    #[cfg(feature = "image")]
+    let mut thumbnails = std::mem::take(&mut self.thumbnails);
+
+    let parent = self.parent_entries();
+    for row in range {
+      ui.horizontal(|ui| {
+        let start = row * columns;
+        let end = cmp::min(start + columns, parent + self.visible.len());
+        for slot in start..end {
+          let (rect, response) = ui.allocate_exact_size(tile_size, egui::Sense::click());
+          let thumb_rect = egui::Rect::from_center_size(
+            egui::pos2(
+              rect.center().x,
+              rect.top() + 4.0 + self.thumbnail_size.y / 2.0,
+            ),
+            self.thumbnail_size,
+          );
+
+          if slot < parent {
+            paint_tile(ui, rect, false, response.hovered());
+            if self.show_icons {
+              paint_tile_icon(ui, thumb_rect, self.icons.folder);
+            }
+            paint_tile_name(ui, rect, thumb_rect, "..");
+            if response.double_clicked() {
+              command = Some(Command::UpDirectory);
+            }
+            continue;
+          }
+
+          let idx = self.visible[slot - parent];
+          let info = &files[idx];
+          let is_selected = if self.multi_select_enabled {
+            info.selected
+          } else {
+            Some(&info.path) == selected
+          };
+          paint_tile(ui, rect, is_selected, response.hovered());
+          self.paint_focus(ui, rect, idx);
+
+          #[cfg(feature = "image")]
+          let texture = match thumbnails.get(&info.path) {
+            Some(texture) => texture.clone(),
+            None if decode_budget > 0 && is_image(&info.path) => {
+              decode_budget -= 1;
+              let texture = load_thumbnail(ui.ctx(), &info.path, self.thumbnail_size);
+              thumbnails.insert(info.path.clone(), texture.clone());
+              texture
+            }
+            None => None,
+          };
+
+          #[cfg(not(feature = "image"))]
+          let texture: Option<egui::TextureHandle> = None;
+
+          match texture {
+            Some(texture) => {
+              let size = texture.size_vec2();
+              let scale = (thumb_rect.width() / size.x).min(thumb_rect.height() / size.y);
+              let image_rect = egui::Rect::from_center_size(thumb_rect.center(), size * scale);
+              let uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+              ui.painter()
+                .image(texture.id(), image_rect, uv, egui::Color32::WHITE);
+            }
+            None if self.show_icons => paint_tile_icon(ui, thumb_rect, self.icons.entry(info)),
+            None => {}
+          }
+          paint_tile_name(ui, rect, thumb_rect, &self.entry_name(info));
+          let response = response.on_hover_ui(|ui| {
+            ui.label(self.hover_text(info));
+          });
+
+          if scroll_to == Some(idx) {
+            response.scroll_to_me(Some(egui::Align::Center));
+          }
+
+          if let Some(cmd) = self.entry_command(ui, &response, idx, info) {
+            command = Some(cmd);
+          }
+        }
+      });
+    }
+
+    #[cfg(feature = "image")]
+    {
+      self.thumbnails = thumbnails;
+      if decode_budget == 0 {
+        // More thumbnails might need decoding.
+        ui.ctx().request_repaint();
+      }
+    }
+
+    command
+  }
+
+  fn has_sidebar(&self) -> bool {
+    #[cfg(windows)]
+    if !self.known_folders.is_empty() {
+      return true;
+    }
+
+    #[cfg(unix)]
+    if !self.mounts.is_empty() {
+      return true;
+    }
+
+    self.show_sidebar || !self.quick_access.is_empty()
+  }
+
+  /// Quick access, bookmarks and, on Windows, known folders. Clicking one opens it.
+  fn sidebar_ui(&mut self, ui: &mut Ui) {
+    let mut open = None;
+
+    for (name, path) in &self.quick_access {
+      let response = ui
+        .selectable_label(self.path == *path, name)
+        .on_hover_text(self.display_path(path));
+      if response.clicked() {
+        open = Some(path.clone());
+      }
+    }
+
+    if self.show_sidebar {
+      if !self.quick_access.is_empty() {
+        ui.separator();
+      }
+
+      ui.horizontal(|ui| {
+        ui.strong(self.labels.bookmarks);
+        ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
+          let bookmarked = self.bookmarks.contains(&self.path);
+          let response = ui
+            .add_enabled(!bookmarked, egui::Button::new("+").small())
+            .on_hover_text(self.labels.bookmark_hover);
+          if response.clicked() {
+            self.add_bookmark(self.path.clone());
+          }
+        });
+      });
+
+      let mut remove = None;
+      for path in &self.bookmarks {
+        ui.horizontal(|ui| {
+          ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
+            if ui.small_button("✕").clicked() {
+              remove = Some(path.clone());
+            }
+            let name = match path.file_name() {
+              Some(name) => name.to_string_lossy().to_string(),
+              None => self.display_path(path),
+            };
+            let response = ui
+              .add_sized(
+                ui.available_size(),
+                egui::SelectableLabel::new(self.path == *path, name),
+              )
+              .on_hover_text(self.display_path(path));
+            if response.clicked() {
+              open = Some(path.clone());
+            } else if response.secondary_clicked() {
+              remove = Some(path.clone());
+            }
+          });
+        });
+      }
+
+      if let Some(path) = remove {
+        self.remove_bookmark(&path);
+      }
+    }
+
+    #[cfg(unix)]
+    if !self.mounts.is_empty() {
+      if self.show_sidebar || !self.quick_access.is_empty() {
+        ui.separator();
+      }
+      for path in &self.mounts {
+        let name = match path.file_name() {
+          Some(name) => name.to_string_lossy().to_string(),
+          None => self.display_path(path),
+        };
+        let response = ui
+          .selectable_label(
+            self.path == *path,
+            self.entry_label(self.icons.mount, &name),
+          )
+          .on_hover_text(self.display_path(path));
+        if response.clicked() {
+          open = Some(path.clone());
+        }
+      }
+    }
+
+    #[cfg(windows)]
+    if !self.known_folders.is_empty() {
+      if self.show_sidebar || !self.quick_access.is_empty() {
+        ui.separator();
+      }
+      for (name, path) in &self.known_folders {
+        let response = ui
+          .selectable_label(self.path == *path, *name)
+          .on_hover_text(path.to_string_lossy());
+        if response.clicked() {
+          open = Some(path.clone());
+        }
+      }
+    }
+
+    // Go through the same checks as opening a folder in the list.
+    if let Some(path) = open {
+      self.apply_command(Command::BrowseDirectory(self.file_system.entry(&path)));
+    }
+  }
+
+  /// Tree of folders, expanded along the current path when it changes. Clicking a folder opens
+  /// it.
+  fn tree_ui(&mut self, ui: &mut Ui) {
+    let reveal = self.tree_path.as_ref() != Some(&self.path);
+    self.tree_path = Some(self.path.clone());
+
+    #[cfg(windows)]
+    let roots = get_drives();
+    #[cfg(not(windows))]
+    let roots = vec![PathBuf::from("/")];
+
+    let mut open = None;
+    ScrollArea::both().auto_shrink(false).show(ui, |ui| {
+      for root in roots {
+        self.tree_node_ui(ui, &root, reveal, &mut open);
+      }
+    });
+
+    if let Some(path) = open {
+      self.apply_command(Command::BrowseDirectory(self.file_system.entry(&path)));
+    }
+  }
+
+  /// Node of the folder at `path` in the tree, with its subfolders if it is expanded.
+  fn tree_node_ui(&mut self, ui: &mut Ui, path: &Path, reveal: bool, open: &mut Option<PathBuf>) {
+    let id = Id::new(("egui_file_tree", path));
+    let mut state =
+      egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, false);
+    if reveal && self.path.starts_with(path) && self.path != path {
+      state.set_open(true);
+    }
+
+    let name = match path.file_name() {
+      Some(name) => name.to_string_lossy().to_string(),
+      None => self.display_path(path),
+    };
+    let current = self.path == path;
+    let (_, header, _) = state
+      .show_header(ui, |ui| ui.selectable_label(current, name))
+      .body(|ui| {
+        for child in self.tree_children(path) {
+          self.tree_node_ui(ui, &child, reveal, open);
+        }
+      });
+
+    if header.inner.clicked() {
+      *open = Some(path.to_path_buf());
+    }
+    if reveal && current {
+      header.inner.scroll_to_me(Some(egui::Align::Center));
+    }
+  }
+
+  /// Subfolders of the folder at `path` shown in the tree, read when they are first needed.
+  fn tree_children(&mut self, path: &Path) -> Vec<PathBuf> {
+    if let Some(children) = self.tree.get(path) {
+      return children.clone();
+    }
+
+    let entries = self.file_system.read_dir(path, true).unwrap_or_default();
+    let mut children: Vec<PathBuf> = entries
+      .into_iter()
+      .filter(|info| info.is_dir() && (self.show_hidden || !is_hidden(info)))
+      .filter(|info| (self.show_dirs_filter)(&info.path))
+      .map(|info| info.path)
+      .collect();
+    children.sort_by_cached_key(|path| {
+      let name = path.file_name().unwrap_or_default();
+      name.to_string_lossy().to_lowercase()
+    });
+    self.tree.insert(path.to_path_buf(), children.clone());
+    children
+  }
+
+  fn has_preview(&self) -> bool {
+    #[cfg(feature = "image")]
+    if self.show_preview {
+      return true;
+    }
+
+    self.show_text_preview
+  }
+
+  /// Preview of the selected file, read again only when the selection changes.
+  fn preview_ui(&mut self, ui: &mut Ui) {
+    let path = self
+      .selected_file
+      .as_ref()
+      .filter(|info| !info.is_dir())
+      .map(|info| &info.path);
+
+    #[cfg(feature = "image")]
+    if self.show_preview {
+      if self.preview.as_ref().map(|(path, _)| path) != path {
+        self.preview = path.map(|path| {
+          let texture = match is_image(path) {
+            true => load_thumbnail(ui.ctx(), path, egui::vec2(PREVIEW_SIZE, PREVIEW_SIZE)),
+            false => None,
+          };
+          (path.clone(), texture)
+        });
+      }
+
+      if let Some((_, Some(texture))) = &self.preview {
+        let size = texture.size_vec2();
+        let scale = (ui.available_width() / size.x).min(1.0);
+        ui.centered_and_justified(|ui| ui.image((texture.id(), size * scale)));
+        return;
+      }
+    }
+
+    if self.show_text_preview {
+      if self.text_preview.as_ref().map(|(path, _)| path) != path {
+        let max_bytes = self.preview_max_bytes;
+        self.text_preview = path.map(|path| (path.clone(), read_text_preview(path, max_bytes)));
+      }
+
+      if let Some((_, text)) = &self.text_preview {
+        match text {
+          Ok(Some(text)) => {
+            ScrollArea::both().show(ui, |ui| {
+              ui.add(egui::Label::new(RichText::new(text).monospace()).extend());
+            });
+          }
+          Ok(None) => {
+            ui.centered_and_justified(|ui| ui.weak(self.labels.binary_file));
+          }
+          Err(err) => {
+            let text = fill(self.labels.preview_error, &[("error", err)]);
+            ui.centered_and_justified(|ui| ui.weak(text));
+          }
+        }
+        return;
+      }
+    }
+
+    ui.centered_and_justified(|ui| ui.weak(self.labels.no_preview));
+  }
+
+  /// Buttons for the folders of the current path, returning the one that was clicked. Clicking
+  /// the rest of the bar switches to the text field.
+  fn breadcrumb_ui(&mut self, ui: &mut Ui) -> Option<PathBuf> {
+    let mut clicked = None;
+    let size = egui::vec2(ui.available_width(), ui.spacing().interact_size.y);
+    let layout = Layout::left_to_right(egui::Align::Center);
+    let response = ui
+      .allocate_ui_with_layout(size, layout, |ui| {
+        ui.spacing_mut().item_spacing.x = 2.0;
+        let mut ancestors: Vec<&Path> = self.path.ancestors().collect();
+        ancestors.reverse();
+        for (i, path) in ancestors.into_iter().enumerate() {
+          if i > 1 {
+            ui.label(self.display_separator.to_string());
+          }
+          // The root, or drive on Windows, has no file name.
+          let label = match path.file_name() {
+            Some(name) => name.to_string_lossy().to_string(),
+            None => self.display_path(path),
+          };
+          if ui.button(label).clicked() {
+            clicked = Some(path.to_path_buf());
+          }
+        }
+        ui.allocate_response(ui.available_size(), egui::Sense::click())
+      })
+      .inner;
+
+    if response.clicked() {
+      self.editing_path = true;
+    }
+    clicked
+  }
+
+  /// Number of entries shown before the folder contents, which is one for the ".." entry.
+  fn parent_entries(&self) -> usize {
+    match self.show_parent_entry && self.path.parent().is_some() {
+      true => 1,
+      false => 0,
+    }
+  }
+
+  /// Height of a tile in the grid view.
+  fn tile_height(&self, ui: &Ui) -> f32 {
+    self.thumbnail_size.y + ui.text_style_height(&egui::TextStyle::Body) + 8.0
+  }
+
+  /// Command for clicks on the entry at `idx` of the file list.
+  fn entry_command(
+    &self,
+    ui: &Ui,
+    response: &egui::Response,
+    idx: usize,
+    info: &FileInfo,
+  ) -> Option<Command> {
+    if let Some(cmd) = self.context_menu(response, info) {
+      return Some(cmd);
+    }
+
+    if response.double_clicked() {
+      return self.activate_command(info);
+    }
+
+    if !response.clicked() {
+      return None;
+    }
+
+    if !self.multi_select_enabled {
+      return Some(Command::Select(info.clone()));
+    }
+
+    Some(if ui.input(|i| i.modifiers.shift) {
+      Command::MultiSelectRange(idx)
+    } else if ui.input(|i| i.modifiers.ctrl) {
+      Command::MultiSelectSwitch(idx)
     } else {
-      !self.filename_edit.is_empty() && (self.filename_filter)(self.filename_edit.as_str())
+      Command::MultiSelect(idx)
+    })
+  }
+
+  /// Header button that sorts by `sort_by`, or reverses the order if it already does.
+  fn sort_header_ui(&mut self, ui: &mut Ui, sort_by: SortKey, rect: Option<egui::Rect>) {
+    let label = sort_by.label(&self.labels);
+    let text = match (self.sort_by == sort_by, self.sort_reverse) {
+      (true, false) => format!("{label} ⏶"),
+      (true, true) => format!("{label} ⏷"),
+      (false, _) => label.to_string(),
+    };
+
+    let label = egui::SelectableLabel::new(self.sort_by == sort_by, text);
+    let response = match rect {
+      // Names are aligned left and the other columns right, like in the rows.
+      Some(rect) => {
+        let layout = match sort_by {
+          SortKey::Name => Layout::left_to_right(egui::Align::Center),
+          _ => Layout::right_to_left(egui::Align::Center),
+        };
+        let builder = egui::UiBuilder::new().max_rect(rect).layout(layout);
+        ui.allocate_new_ui(builder, |ui| ui.add(label)).inner
+      }
+      None => ui.add(label),
+    };
+
+    if response.clicked() {
+      self.sort_reverse = self.sort_by == sort_by && !self.sort_reverse;
+      self.sort_by = sort_by;
+      self.sort_files();
+    }
+  }
+
+  /// Add `text` to the typed text, which is reset after a second without typing, and focus the
+  /// first entry starting with it. Typing the same letter again moves to the next such entry.
+  fn type_ahead(&mut self, text: &str, time: f64) {
+    if time - self.type_ahead_time > TYPE_AHEAD_TIMEOUT {
+      self.type_ahead.clear();
+    }
+    self.type_ahead_time = time;
+    self.type_ahead.push_str(&text.to_lowercase());
+
+    let Ok(files) = &self.files else {
+      return;
+    };
+
+    let count = self.visible.len();
+    let current = self
+      .focused_index
+      .and_then(|idx| self.visible.iter().position(|&i| i == idx));
+
+    // Repeating a single letter cycles through the entries starting with it.
+    let mut chars = self.type_ahead.chars();
+    let first = chars.next();
+    let repeated = chars.all(|c| Some(c) == first);
+    let prefix = match repeated {
+      true => &self.type_ahead[..first.map_or(0, char::len_utf8)],
+      false => self.type_ahead.as_str(),
+    };
+    let start = match (current, repeated) {
+      (Some(current), true) => current + 1,
+      (Some(current), false) => current,
+      (None, _) => 0,
+    };
+
+    let found = (0..count).map(|n| (start + n) % count).find(|&position| {
+      let idx = self.visible[position];
+      get_file_name(&files[idx])
+        .to_lowercase()
+        .starts_with(prefix)
+    });
+    if let Some(position) = found {
+      self.focus(position, false);
+    }
+  }
+
+  /// Outline the entry at `idx` if it has the keyboard focus in multi-select mode, where it might
+  /// not be selected.
+  fn paint_focus(&self, ui: &Ui, rect: egui::Rect, idx: usize) {
+    if self.multi_select_enabled && self.focused_index == Some(idx) {
+      let visuals = ui.visuals();
+      ui.painter().rect_stroke(
+        rect,
+        visuals.widgets.hovered.rounding,
+        visuals.widgets.hovered.bg_stroke,
+      );
+    }
+  }
+
+  /// Paint the optional columns in their places in the row at `rect`, clipped to their width.
+  fn paint_columns(&self, ui: &Ui, rect: egui::Rect, info: &FileInfo, now: SystemTime) {
+    let font = egui::TextStyle::Body.resolve(ui.style());
+    let color = ui.visuals().weak_text_color();
+
+    let paint = |column_rect: egui::Rect, text: &str| {
+      let pos = egui::pos2(column_rect.right(), column_rect.center().y);
+      ui.painter()
+        .with_clip_rect(column_rect.intersect(ui.clip_rect()))
+        .text(pos, Align2::RIGHT_CENTER, text, font.clone(), color);
+    };
+
+    for (column, column_rect) in self.column_rects(ui, rect) {
+      match column {
+        Column::Size => match info.is_dir() {
+          true => paint(column_rect, "—"),
+          false => paint(
+            column_rect,
+            &info
+              .metadata()
+              .map_or_else(String::new, |meta| format_size(meta.len())),
+          ),
+        },
+        Column::Modified => paint(column_rect, &self.time_text(info, now)),
+        #[cfg(unix)]
+        Column::Permissions => {
+          use std::os::unix::fs::PermissionsExt;
+
+          paint(
+            column_rect,
+            &info.metadata().map_or_else(
+              || "—".to_string(),
+              |meta| format_permissions(meta.permissions().mode()),
+            ),
+          )
+        }
+      }
+    }
+  }
+
+  /// Modification time of `info` as it is shown in the list. It is formatted once, or once a
+  /// minute for relative times, rather than every frame.
+  fn time_text<'a>(&self, info: &'a FileInfo, now: SystemTime) -> Ref<'a, str> {
+    let minute = match self.date_display {
+      DateDisplay::Absolute => 0,
+      DateDisplay::Relative => now
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() / 60 + 1),
+    };
+
+    let outdated = !matches!(&*info.time_text.borrow(), Some((cached, _)) if *cached == minute);
+    if outdated {
+      let text = info.modified().map_or_else(String::new, |time| {
+        let relative = match self.date_display {
+          DateDisplay::Absolute => None,
+          DateDisplay::Relative => format_relative_time(time, now, &self.labels),
+        };
+        relative.unwrap_or_else(|| format_time(time, &self.date_items))
+      });
+      *info.time_text.borrow_mut() = Some((minute, text));
+    }
+
+    Ref::map(info.time_text.borrow(), |cached| {
+      cached.as_ref().map_or("", |(_, text)| text.as_str())
+    })
+  }
+
+  /// Columns right of the names in the list and details views, from right to left.
+  fn columns(&self) -> Vec<Column> {
+    let details = self.view_mode == ViewMode::Details;
+    let mut columns = Vec::new();
+    if self.show_size || details {
+      columns.push(Column::Size);
+    }
+    if self.show_modified || details {
+      columns.push(Column::Modified);
+    }
+    #[cfg(unix)]
+    if self.show_permissions && details {
+      columns.push(Column::Permissions);
+    }
+    columns
+  }
+
+  /// The columns with their place in a row at `rect`, from right to left.
+  fn column_rects(&self, ui: &Ui, rect: egui::Rect) -> Vec<(Column, egui::Rect)> {
+    let mut right = rect.right() - ui.spacing().button_padding.x;
+    let columns = self.columns().into_iter().map(|column| {
+      let left = right - column.width();
+      let column_rect = egui::Rect::from_x_y_ranges(left..=right, rect.y_range());
+      right = left;
+      (column, column_rect)
+    });
+    columns.collect()
+  }
+
+  /// The entry that Enter acts upon while the list has the focus.
+  fn highlighted(&self) -> Option<&FileInfo> {
+    let focused = match (&self.files, self.focused_index) {
+      (Ok(files), Some(idx)) => files.get(idx),
+      _ => None,
+    };
+    focused.or(self.selected_file.as_ref())
+  }
+
+  /// Command for activating an entry by double click or Enter: folders are entered, files are
+  /// opened or saved depending on the dialog type, provided the name passes the filter.
+  fn activate_command(&self, info: &FileInfo) -> Option<Command> {
+    if info.is_dir() {
+      return Some(Command::BrowseDirectory(info.clone()));
+    }
+
+    if !(self.filename_filter)(get_file_name(info)) {
+      return None;
+    }
+
+    match self.dialog_type {
+      // A file can't be chosen as a folder.
+      DialogType::SelectFolder => None,
+      DialogType::OpenFile => Some(Command::Open(info.clone())),
+      DialogType::SaveFile => Some(Command::Save(info.clone())),
+    }
+  }
+
+  fn ui_in_window(&mut self, ui: &mut Ui) {
+    let mut command: Option<Command> = None;
+    self.poll_listing();
+    self.update_write_error();
+    #[cfg(feature = "watch")]
+    self.poll_watch(ui.ctx());
+    let rect = ui.max_rect();
+
+    // An inline rename ends when leaving its folder.
+    if let Some(rename) = &self.inline_rename {
+      if rename.path.parent() != Some(self.path.as_path()) {
+        self.inline_rename = None;
+      }
     }
-  }
 
-  fn can_rename(&self) -> bool {
-    if !self.filename_edit.is_empty() {
-      if let Some(file) = &self.selected_file {
-        return get_file_name(file) != self.filename_edit;
-      }
+    // The keys below only act on the list while it has the focus, which it gets when the dialog
+    // opens or is clicked outside of its text fields.
+    let list_id = ui.id().with("egui_file_list");
+    let list = ui.interact(rect, list_id, egui::Sense::focusable_noninteractive());
+    if std::mem::take(&mut self.focus_list) {
+      list.request_focus();
+    }
+    let list_has_focus = list.has_focus();
+    if list_has_focus {
+      let filter = egui::EventFilter {
+        horizontal_arrows: true,
+        vertical_arrows: true,
+        escape: true,
+        ..Default::default()
+      };
+      ui.memory_mut(|mem| mem.set_focus_lock_filter(list_id, filter));
     }
-    false
-  }
 
-  /// Shows the dialog if it is open. It is also responsible for state management.
-  /// Should be called every ui update.
-  pub fn show(&mut self, ctx: &Context) -> &Self {
-    self.state = match self.state {
-      State::Open => {
-        if ctx.input(|state| state.key_pressed(Key::Escape)) {
-          self.state = State::Cancelled;
-        }
+    // Enter activates the highlighted entry.
+    if list_has_focus && ui.input_mut(|state| state.consume_key(egui::Modifiers::NONE, Key::Enter))
+    {
+      command = self
+        .highlighted()
+        .and_then(|info| self.activate_command(info));
+    }
 
-        let mut is_open = true;
-        self.ui(ctx, &mut is_open);
-        match is_open {
-          true => self.state,
-          false => State::Cancelled,
+    // Arrow, page and Home/End keys move the focus, Shift extends a multi selection.
+    if list_has_focus {
+      let columns = match self.view_mode {
+        ViewMode::Grid { columns } => cmp::max(columns, 1),
+        _ => 1,
+      };
+      let page = cmp::max(self.page_rows, 1) * columns;
+      let (key, shift) = ui.input_mut(|state| {
+        let shift = state.modifiers.shift;
+        let key = [
+          Key::ArrowUp,
+          Key::ArrowDown,
+          Key::ArrowLeft,
+          Key::ArrowRight,
+          Key::PageUp,
+          Key::PageDown,
+          Key::Home,
+          Key::End,
+        ]
+        .into_iter()
+        .find(|&key| state.consume_key(egui::Modifiers::NONE, key));
+        (key, shift)
+      });
+      let position = self
+        .focused_index
+        .and_then(|idx| self.visible.iter().position(|&i| i == idx));
+      let last = self.visible.len().saturating_sub(1);
+      let target = match (key, position) {
+        (Some(Key::Home), _) => Some(0),
+        (Some(Key::End), _) => Some(last),
+        (Some(_), None) => Some(0),
+        (Some(Key::PageUp), Some(position)) => Some(position.saturating_sub(page)),
+        (Some(Key::PageDown), Some(position)) => Some(cmp::min(position + page, last)),
+        (Some(Key::ArrowUp), Some(position)) => Some(position.saturating_sub(columns)),
+        (Some(Key::ArrowDown), Some(position)) => Some(cmp::min(position + columns, last)),
+        (Some(Key::ArrowLeft), Some(position)) if columns > 1 => Some(position.saturating_sub(1)),
+        (Some(Key::ArrowRight), Some(position)) if columns > 1 => {
+          Some(cmp::min(position + 1, last))
         }
+        _ => None,
+      };
+      if let Some(target) = target {
+        self.focus(target, shift);
       }
-      _ => State::Closed,
-    };
-
-    self
-  }
-
-  fn ui(&mut self, ctx: &Context, is_open: &mut bool) {
-    let mut window = Window::new(RichText::new(&self.title).strong())
-      .open(is_open)
-      .default_size(self.default_size)
-      .resizable(self.resizable)
-      .collapsible(false);
+    }
 
-    if let Some(id) = self.id {
-      window = window.id(id);
+    // Typing jumps to the next entry whose name starts with the typed text.
+    if list_has_focus {
+      let (text, time) = ui.input_mut(|state| {
+        let mut text = String::new();
+        state.events.retain(|event| match event {
+          egui::Event::Text(typed) => {
+            text.push_str(typed);
+            false
+          }
+          _ => true,
+        });
+        (text, state.time)
+      });
+      if !text.is_empty() {
+        self.type_ahead(&text, time);
+      }
     }
 
-    if let Some((align, offset)) = self.anchor {
-      window = window.anchor(align, offset);
+    // F2 renames the highlighted entry.
+    if list_has_focus
+      && self.rename
+      && ui.input_mut(|state| state.consume_key(egui::Modifiers::NONE, Key::F2))
+    {
+      if let Some(info) = self.highlighted() {
+        command = Some(Command::StartRename(info.clone()));
+      }
     }
 
-    if let Some(current_pos) = self.current_pos {
-      window = window.current_pos(current_pos);
+    // Ctrl+C copies the selected paths.
+    if list_has_focus {
+      let copy = ui.input_mut(|state| {
+        let len = state.events.len();
+        state.events.retain(|event| *event != egui::Event::Copy);
+        state.events.len() != len
+      });
+      if copy {
+        self.copy_selection_to_clipboard(ui.ctx());
+      }
     }
 
-    if let Some(default_pos) = self.default_pos {
-      window = window.default_pos(default_pos);
+    // Ctrl+A selects all files.
+    if list_has_focus
+      && self.multi_select_enabled
+      && ui.input_mut(|state| state.consume_key(egui::Modifiers::COMMAND, Key::A))
+    {
+      command = Some(Command::SelectAll);
     }
 
-    window.show(ctx, |ui| {
-      if self.keep_on_top {
-        ui.ctx().move_to_top(ui.layer_id());
+    // Ctrl+H shows or hides hidden files.
+    if let Some(shortcut) = self.show_hidden_shortcut.filter(|_| list_has_focus) {
+      if ui.input_mut(|state| state.consume_shortcut(&shortcut)) {
+        self.set_show_hidden(!self.show_hidden);
       }
-      self.ui_in_window(ui)
-    });
-  }
+    }
 
-  fn ui_in_window(&mut self, ui: &mut Ui) {
-    enum Command {
-      Cancel,
-      CreateDirectory,
-      Folder,
-      Open(FileInfo),
-      OpenSelected,
-      BrowseDirectory(FileInfo),
-      Refresh,
-      Rename(PathBuf, PathBuf),
-      Save(FileInfo),
-      Select(FileInfo),
-      MultiSelectRange(usize),
-      MultiSelect(usize),
-      MultiSelectSwitch(usize),
-      UpDirectory,
+    // Ctrl+1, 2 and 3 switch the view mode.
+    if list_has_focus {
+      for view_mode in ViewMode::ALL {
+        let shortcut = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, view_mode.shortcut());
+        if ui.input_mut(|state| state.consume_shortcut(&shortcut)) {
+          self.set_view_mode(view_mode);
+        }
+      }
     }
-    let mut command: Option<Command> = None;
 
     // Top directory field with buttons.
     egui::TopBottomPanel::top("egui_file_top").show_inside(ui, |ui| {
       ui.horizontal(|ui| {
         ui.add_enabled_ui(self.path.parent().is_some(), |ui| {
           let response = ui
-            .button("⬆")
-            .on_hover_text(self.parent_folder_button_hover_text);
+            .button(self.icon_or_text(
+              self.icons.parent_folder,
+              self.labels.parent_folder_button_hover,
+            ))
+            .on_hover_text(self.labels.parent_folder_button_hover);
           if response.clicked() {
             command = Some(Command::UpDirectory);
           }
         });
+        ui.add_enabled_ui(self.recent.len() > 1, |ui| {
+          let mut recent = None;
+          let text = self.icon_or_text(self.icons.recent, self.labels.recent_button);
+          ui.menu_button(text, |ui| {
+            for path in self.recent.iter().skip(1) {
+              if ui.button(self.display_path(path)).clicked() {
+                recent = Some(path.clone());
+                ui.close_menu();
+              }
+            }
+          });
+          if let Some(path) = recent {
+            command = Some(Command::BrowseDirectory(self.file_system.entry(&path)));
+          }
+        });
         ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
-          let response = ui.button("⟲").on_hover_text(self.refresh_button_hover_text);
+          let response = ui
+            .button(self.icon_or_text(self.icons.refresh, self.labels.refresh_button_hover))
+            .on_hover_text(self.labels.refresh_button_hover);
           if response.clicked() {
             command = Some(Command::Refresh);
           }
 
+          #[cfg(feature = "open")]
+          if ui
+            .button(self.icon_or_text(self.icons.file_manager, self.labels.show_in_file_manager))
+            .on_hover_text(self.labels.show_in_file_manager)
+            .clicked()
+          {
+            command = Some(Command::ShowInFileManager(self.path.clone()));
+          }
+
+          for view_mode in ViewMode::ALL.into_iter().rev() {
+            let selected = self.view_mode.same_mode(view_mode);
+            let text = self.icon_or_text(
+              self.icons.view_mode(view_mode),
+              view_mode.label(&self.labels),
+            );
+            if ui.selectable_label(selected, text).clicked() {
+              self.set_view_mode(view_mode);
+            }
+          }
+
+          if self.path_bar_mode == PathBarMode::Breadcrumb && !self.editing_path {
+            if let Some(path) = self.breadcrumb_ui(ui) {
+              command = Some(Command::BrowseDirectory(self.file_system.entry(&path)));
+            }
+            return;
+          }
+
+          // Tab only stays in the field when there is something to complete.
           let response = ui.add_sized(
             ui.available_size(),
-            TextEdit::singleline(&mut self.path_edit),
+            TextEdit::singleline(&mut self.path_edit).lock_focus(!self.path_suggestions.is_empty()),
           );
+          if self.editing_path && !response.has_focus() && !response.lost_focus() {
+            // Just switched from the breadcrumbs.
+            response.request_focus();
+          }
+          if response.changed() {
+            self.path_suggestions = self.path_suggestions(&self.path_edit);
+          }
 
-          if response.lost_focus() {
-            let path = PathBuf::from(&self.path_edit);
-            command = Some(Command::Open(FileInfo::new(path)));
+          // Clicking a suggestion completes the path without opening it yet. Tab does the same
+          // with a single suggestion, or completes the part that all suggestions have in common.
+          let mut accepted = None;
+          if response.has_focus() && ui.input(|state| state.key_pressed(Key::Tab)) {
+            match self.path_suggestions.as_slice() {
+              [path] => accepted = Some(path.clone()),
+              [first, rest @ ..] => {
+                let mut common = self.display_path(first);
+                for path in rest {
+                  let text = self.display_path(path);
+                  let len = common
+                    .char_indices()
+                    .zip(text.chars())
+                    .find(|((_, a), b)| a != b)
+                    .map_or(cmp::min(common.len(), text.len()), |((idx, _), _)| idx);
+                  common.truncate(len);
+                }
+                if common.len() > self.path_edit.len() {
+                  self.path_edit = common;
+                  set_cursor_to_end(ui.ctx(), response.id, &self.path_edit);
+                }
+              }
+              [] => {}
+            }
+          }
+
+          let popup_id = ui.make_persistent_id("egui_file_path_suggestions");
+          if response.has_focus() && !self.path_suggestions.is_empty() {
+            ui.memory_mut(|mem| mem.open_popup(popup_id));
+          }
+          let close_behavior = egui::PopupCloseBehavior::CloseOnClickOutside;
+          egui::popup_below_widget(ui, popup_id, &response, close_behavior, |ui| {
+            ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+              for path in &self.path_suggestions {
+                if ui
+                  .selectable_label(false, self.display_path(path))
+                  .clicked()
+                {
+                  accepted = Some(path.clone());
+                }
+              }
+            });
+          });
+
+          if let Some(path) = accepted {
+            self.path_edit = self.display_path(&path);
+            self.path_edit.push(self.display_separator);
+            self.path_suggestions = self.path_suggestions(&self.path_edit);
+            set_cursor_to_end(ui.ctx(), response.id, &self.path_edit);
+            response.request_focus();
+          } else if response.lost_focus() {
+            // Clicking a suggestion takes the focus before the click is complete.
+            let popup_layer = egui::LayerId::new(egui::Order::Foreground, popup_id);
+            let on_popup = ui
+              .ctx()
+              .pointer_interact_pos()
+              .is_some_and(|pos| ui.ctx().layer_id_at(pos) == Some(popup_layer));
+            if !on_popup {
+              ui.memory_mut(|mem| mem.close_popup());
+              self.editing_path = false;
+              let path = self.parse_path(&self.path_edit);
+              command = Some(Command::EnterPath(path));
+            }
           }
         });
       });
@@ -659,18 +4888,59 @@ impl FileDialog {
     // Bottom file field.
     egui::TopBottomPanel::bottom("egui_file_bottom").show_inside(ui, |ui| {
       ui.add_space(ui.spacing().item_spacing.y * 2.0);
+      if let Some(error) = &self.last_error {
+        let mut dismiss = false;
+        ui.horizontal(|ui| {
+          dismiss = ui.small_button("✕").clicked();
+          ui.colored_label(ui.visuals().error_fg_color, error);
+        });
+        if dismiss {
+          self.last_error = None;
+        }
+        ui.add_space(ui.spacing().item_spacing.y);
+      }
       ui.horizontal(|ui| {
-        ui.label(self.file_label_text);
+        ui.label(self.labels.file_label);
         ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
-          if self.new_folder && ui.button(self.new_folder_button_text).clicked() {
-            command = Some(Command::CreateDirectory);
+          if self.new_folder && ui.button(self.labels.new_folder_button).clicked() {
+            command = Some(Command::NewFolder);
+          }
+
+          if self.delete && self.multi_select_enabled {
+            ui.add_enabled_ui(self.selected_count() > 0, |ui| {
+              if ui.button(self.labels.delete_button).clicked() {
+                command = Some(Command::DeleteSelection);
+              }
+            });
+          } else if self.delete {
+            ui.add_enabled_ui(self.selected_file.is_some(), |ui| {
+              if ui.button(self.labels.delete_button).clicked() {
+                if let Some(file) = &self.selected_file {
+                  command = Some(Command::Delete(file.path.clone()));
+                }
+              }
+            });
+          }
+
+          if !self.filters.is_empty() {
+            let mut active_filter = self.active_filter;
+            egui::ComboBox::from_id_salt("egui_file_filter")
+              .selected_text(self.active_filter_name().unwrap_or_default())
+              .show_ui(ui, |ui| {
+                for (idx, (name, _, _)) in self.filters.iter().enumerate() {
+                  ui.selectable_value(&mut active_filter, idx, name);
+                }
+              });
+            if active_filter != self.active_filter {
+              self.set_active_filter(active_filter);
+            }
           }
 
           if self.rename {
             ui.add_enabled_ui(self.can_rename(), |ui| {
-              if ui.button(self.rename_button_text).clicked() {
+              if ui.button(self.labels.rename_button).clicked() {
                 if let Some(from) = self.selected_file.clone() {
-                  let to = from.path.with_file_name(&self.filename_edit);
+                  let to = from.path.with_file_name(self.filename());
                   command = Some(Command::Rename(from.path, to));
                 }
               }
@@ -681,33 +4951,34 @@ impl FileDialog {
             ui.available_size(),
             TextEdit::singleline(&mut self.filename_edit),
           );
+          if std::mem::take(&mut self.focus_filename) {
+            response.request_focus();
+          }
 
           if response.lost_focus() {
             let ctx = response.ctx;
             let enter_pressed = ctx.input(|state| state.key_pressed(Key::Enter));
 
-            if enter_pressed && (self.filename_filter)(self.filename_edit.as_str()) {
-              let path = self.path.join(&self.filename_edit);
+            if enter_pressed && (self.filename_filter)(self.filename()) {
+              let path = self.path.join(self.filename());
               match self.dialog_type {
                 DialogType::SelectFolder => command = Some(Command::Folder),
                 DialogType::OpenFile => {
-                  if path.exists() {
-                    command = Some(Command::Open(FileInfo::new(path)));
+                  if self.file_system.exists(&path) {
+                    command = Some(Command::Open(self.file_system.entry(&path)));
                   }
                 }
-                DialogType::SaveFile => {
-                  let file_info = FileInfo::new(path);
-                  command = Some(match file_info.is_dir() {
-                    true => Command::Open(file_info),
-                    false => Command::Save(file_info),
-                  });
-                }
+                DialogType::SaveFile => command = Some(Command::SaveTyped),
               }
             }
           }
         });
       });
 
+      if let Some(warning) = self.filename_warning() {
+        ui.colored_label(ui.visuals().warn_fg_color, warning);
+      }
+
       ui.add_space(ui.spacing().item_spacing.y);
 
       // Confirm, Cancel buttons.
@@ -715,7 +4986,7 @@ impl FileDialog {
         match self.dialog_type {
           DialogType::SelectFolder => {
             ui.horizontal(|ui| {
-              if ui.button(self.open_button_text).clicked() {
+              if ui.button(self.labels.open_button).clicked() {
                 command = Some(Command::Folder);
               };
             });
@@ -726,7 +4997,7 @@ impl FileDialog {
                 ui.disable();
               }
 
-              if ui.button(self.open_button_text).clicked() {
+              if ui.button(self.labels.open_button).clicked() {
                 command = Some(Command::OpenSelected);
               };
             });
@@ -738,172 +5009,673 @@ impl FileDialog {
             };
 
             if should_open_directory {
-              if ui.button(self.open_button_text).clicked() {
+              if ui.button(self.labels.open_button).clicked() {
                 command = Some(Command::OpenSelected);
               };
             } else {
               ui.horizontal(|ui| {
-                if !self.can_save() {
+                let save_error = self.save_error(&self.save_path());
+                if !self.can_save() || save_error.is_some() {
                   ui.disable();
                 }
 
-                if ui.button(self.save_button_text).clicked() {
-                  let filename = &self.filename_edit;
-                  let path = self.path.join(filename);
-                  command = Some(Command::Save(FileInfo::new(path)));
-                };
-              });
-            }
-          }
-        }
+                let mut response = ui.button(self.labels.save_button);
+                if let Some(error) = save_error {
+                  response = response.on_disabled_hover_text(error);
+                }
+                if response.clicked() {
+                  command = Some(Command::SaveTyped);
+                };
+              });
+            }
+          }
+        }
+
+        if ui.button(self.labels.cancel_button).clicked() {
+          command = Some(Command::Cancel);
+        }
+
+        if self.multi_select_enabled {
+          if ui.button(self.labels.select_all_button).clicked() {
+            command = Some(Command::SelectAll);
+          }
+          if ui.button(self.labels.clear_selection_button).clicked() {
+            command = Some(Command::ClearSelection);
+          }
+        }
+
+        if self.multi_select_enabled && self.selection_limits != (0, None) {
+          let count = self.selected_count();
+          let text = match self.selection_limits {
+            (0, Some(max)) => fill(
+              self.labels.selected_max,
+              &[("count", &count), ("max", &max)],
+            ),
+            (min, Some(max)) => fill(
+              self.labels.selected_range,
+              &[("count", &count), ("min", &min), ("max", &max)],
+            ),
+            (min, None) => fill(
+              self.labels.selected_min,
+              &[("count", &count), ("min", &min)],
+            ),
+          };
+          ui.weak(text);
+        }
+
+        ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
+          let mut show_hidden = self.show_hidden;
+          let mut response = ui.checkbox(&mut show_hidden, self.labels.show_hidden_checkbox);
+          if let Some(shortcut) = &self.show_hidden_shortcut {
+            response = response.on_hover_text(ui.ctx().format_shortcut(shortcut));
+          }
+          if response.changed() {
+            self.set_show_hidden(show_hidden);
+          }
+        });
+      });
+
+      // Status line.
+      ui.add_space(ui.spacing().item_spacing.y);
+      ui.weak(self.status_text());
+    });
+
+    // Sidebar with shortcuts.
+    if self.has_sidebar() {
+      egui::SidePanel::left("egui_file_sidebar").show_inside(ui, |ui| {
+        ui.with_layout(ui.layout().with_cross_justify(true), |ui| {
+          self.sidebar_ui(ui);
+        });
+      });
+    }
+
+    // Folder tree.
+    if self.show_tree {
+      egui::SidePanel::left("egui_file_tree").show_inside(ui, |ui| self.tree_ui(ui));
+    }
+
+    // Preview of the selected file.
+    if self.has_preview() {
+      egui::SidePanel::right("egui_file_preview").show_inside(ui, |ui| self.preview_ui(ui));
+    }
+
+    // File list.
+    egui::CentralPanel::default().show_inside(ui, |ui| {
+      if self.show_multi_summary {
+        self.multi_summary_ui(ui);
+        return;
+      }
+
+      // Search field that filters the list, Enter/F3 jumps to the next match and Shift to the
+      // previous one.
+      let response = ui.add(
+        TextEdit::singleline(&mut self.search_query)
+          .hint_text("🔍")
+          .desired_width(f32::INFINITY),
+      );
+      if response.changed() {
+        self.apply_command(Command::Search(self.search_query.clone()));
+      }
+      let (enter, f3, shift) = ui.input(|state| {
+        (
+          state.key_pressed(Key::Enter),
+          state.key_pressed(Key::F3),
+          state.modifiers.shift,
+        )
+      });
+      if (response.lost_focus() && enter) || (response.has_focus() && f3) {
+        self.find_next(shift);
+        response.request_focus();
+      }
+      ui.add_space(ui.spacing().item_spacing.y);
+
+      // Header, clicking a column sorts by it or reverses the order.
+      match self.view_mode {
+        ViewMode::List | ViewMode::Grid { .. } => {
+          ui.horizontal(|ui| {
+            for sort_by in SortKey::ALL {
+              self.sort_header_ui(ui, sort_by, None);
+            }
+          });
+        }
+        ViewMode::Details => {
+          // Placed like the columns of the rows below.
+          let size = Vec2::new(ui.available_width(), ui.spacing().interact_size.y);
+          let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+          let columns = self.column_rects(ui, rect);
+          let mut name_rect = rect;
+          if let Some((_, column_rect)) = columns.last() {
+            name_rect.set_right(column_rect.left());
+          }
+          self.sort_header_ui(ui, SortKey::Name, Some(name_rect));
+          for (column, column_rect) in columns {
+            match column {
+              Column::Size => self.sort_header_ui(ui, SortKey::Size, Some(column_rect)),
+              Column::Modified => self.sort_header_ui(ui, SortKey::Modified, Some(column_rect)),
+              #[cfg(unix)]
+              Column::Permissions => {
+                let layout = Layout::right_to_left(egui::Align::Center);
+                let builder = egui::UiBuilder::new().max_rect(column_rect).layout(layout);
+                ui.allocate_new_ui(builder, |ui| ui.label(self.labels.permissions_header));
+              }
+            }
+          }
+        }
+      }
+      ui.separator();
+
+      if self.loading {
+        ui.vertical_centered(|ui| {
+          ui.add_space(ui.available_height() / 2.0 - ui.spacing().interact_size.y);
+          ui.add(egui::Spinner::new());
+          ui.label(self.labels.loading);
+        });
+        return;
+      }
+
+      let count = self.parent_entries() + self.visible.len();
+      let (row_height, columns) = match self.view_mode {
+        ViewMode::Grid { columns } => (self.tile_height(ui), cmp::max(columns, 1)),
+        _ => (ui.text_style_height(&egui::TextStyle::Body), 1),
+      };
+      let rows = count.div_ceil(columns);
+      let scroll_to_row = self.scroll_to_row.take();
+      let rows_ui = |this: &mut Self, ui: &mut Ui, range, scroll_to| match this.view_mode {
+        ViewMode::Grid { .. } => this.file_grid_ui(ui, range, columns, scroll_to),
+        _ => {
+          // Taken out while the rows borrow the rest of the dialog.
+          let mut inline_rename = this.inline_rename.take();
+          let command = this.file_list_ui(ui, range, scroll_to, &mut inline_rename);
+          this.inline_rename = inline_rename;
+          command
+        }
+      };
+
+      let row_spacing = row_height + ui.spacing().item_spacing.y;
+      self.page_rows = (ui.available_height() / row_spacing) as usize;
+
+      if self.virtualize && count >= VIRTUALIZE_MIN_ROWS {
+        let mut scroll_area = ScrollArea::vertical();
+        let position = scroll_to_row.and_then(|idx| self.visible.iter().position(|&i| i == idx));
+        if let Some(position) = position.map(|position| self.parent_entries() + position) {
+          let offset = (row_height + ui.spacing().item_spacing.y) * (position / columns) as f32;
+          scroll_area = scroll_area.vertical_scroll_offset(offset - ui.available_height() / 2.0);
+        }
+
+        scroll_area.show_rows(ui, row_height, rows, |ui, range| {
+          if let Some(cmd) = rows_ui(self, ui, range, None) {
+            command = Some(cmd);
+          }
+        });
+      } else {
+        ScrollArea::vertical().show(ui, |ui| {
+          if let Some(cmd) = rows_ui(self, ui, 0..rows, scroll_to_row) {
+            command = Some(cmd);
+          }
+        });
+      }
+
+      // Errors are shown in the list instead.
+      if self.files.is_ok() && self.visible.is_empty() {
+        ui.centered_and_justified(|ui| ui.weak(&self.empty_text));
+      }
+    });
+
+    // A click in the dialog that no text field took gives the list the focus back, a click
+    // outside of a modal dialog takes it away.
+    if ui.input(|state| state.pointer.any_click()) {
+      if ui.rect_contains_pointer(rect) {
+        if ui.memory(|mem| mem.focused().is_none()) {
+          list.request_focus();
+        }
+      } else if !self.modal {
+        list.surrender_focus();
+      }
+    }
+
+    if let Some(command) = command {
+      self.apply_command(command);
+    }
+
+    self.toasts_ui(ui, rect);
+  }
+
+  /// Carry out `command` as if the user did it in the dialog. A confirmed selection is reported by
+  /// [`FileDialog::selected`] after the next [`FileDialog::show`]. Only public for tests, it isn't
+  /// covered by semver.
+  #[doc(hidden)]
+  pub fn apply_command(&mut self, command: Command) {
+    match command {
+      Command::AnswerPrompt(false) => self.prompt = None,
+      Command::AnswerPrompt(true) => match self.prompt.take() {
+        Some(Prompt::Overwrite(path)) => {
+          self.selected_file = Some(self.file_system.entry(&path));
+          self.confirm();
+        }
+        Some(Prompt::Delete(path)) => self.delete(path, false),
+        Some(Prompt::DeleteFolder(path, _)) => self.delete(path, true),
+        Some(Prompt::DeleteSelection(paths)) => self.delete_all(paths),
+        Some(Prompt::Discard) => self.state = State::Cancelled,
+        Some(Prompt::NewFolder(name)) => match self.name_error(&name) {
+          None => self.apply_command(Command::CreateDirectory(self.path.join(name))),
+          Some(_) => self.prompt = Some(Prompt::NewFolder(name)),
+        },
+        None => {}
+      },
+      Command::Search(query) => {
+        self.search_query = query;
+        self.search_match = None;
+        self.apply_search();
+      }
+      Command::Select(info) => {
+        self.focused_index = self
+          .files
+          .iter()
+          .flatten()
+          .position(|file| file.path == info.path);
+        self.select(Some(info));
+      }
+      Command::MultiSelect(idx) => {
+        self.focused_index = Some(idx);
+        self.select_reset_multi(idx);
+      }
+      Command::MultiSelectRange(idx) => {
+        self.focused_index = Some(idx);
+        self.select_range(idx);
+      }
+      Command::MultiSelectSwitch(idx) => {
+        self.focused_index = Some(idx);
+        self.select_switch_multi(idx);
+      }
+      Command::StartRename(info) => {
+        if matches!(self.view_mode, ViewMode::Grid { .. }) {
+          // Tiles have no room for a text field, the file name field is used instead. Folders
+          // don't fill it in when selected.
+          get_file_name(&info).clone_into(&mut self.filename_edit);
+          self.focus_filename = true;
+        } else {
+          self.inline_rename = Some(InlineRename {
+            path: info.path.clone(),
+            name: get_file_name(&info).to_string(),
+            focus: true,
+          });
+        }
+        self.select(Some(info));
+      }
+      Command::DeleteSelection => {
+        let paths = self.selection_owned();
+        if !paths.is_empty() {
+          self.prompt = Some(Prompt::DeleteSelection(paths));
+        }
+      }
+      Command::Delete(path) => {
+        // Only the folder itself is read, not its subfolders.
+        let entry = self.file_system.entry(&path);
+        let items = match self.delete_recursive && entry.is_dir() && !entry.is_symlink() {
+          true => self
+            .file_system
+            .read_dir(&path, true)
+            .map_or(0, |entries| entries.len()),
+          false => 0,
+        };
+        self.prompt = Some(match items {
+          0 => Prompt::Delete(path),
+          items => Prompt::DeleteFolder(path, items),
+        });
+      }
+      #[cfg(feature = "open")]
+      Command::ShowInFileManager(path) => {
+        if let Err(err) = show_in_file_manager(&path) {
+          let values: [(&str, &dyn Display); 2] = [("path", &path.display()), ("error", &err)];
+          self.last_error = Some(fill(self.labels.show_error, &values));
+        }
+      }
+      #[cfg(feature = "open")]
+      Command::OpenWithDefaultApp(path) => {
+        // Only launches the file, the dialog stays open.
+        if let Err(err) = open::that_detached(&path) {
+          self.last_error = Some(self.open_error(&path, &err));
+        }
+      }
+      Command::SelectAll => self.select_all(),
+      Command::ClearSelection => self.clear_selection(),
+      Command::Folder => {
+        let path = self.get_folder().to_owned();
+        self.selected_file = Some(self.file_system.entry(&path));
+        self.confirm();
+      }
+      Command::Open(path) => {
+        self.select(Some(path));
+        self.open_selected();
+      }
+      Command::OpenSelected => self.open_selected(),
+      Command::EnterPath(path) => {
+        let entry = self.file_system.entry(&path);
+        if entry.is_dir() || entry.is_file() && self.dialog_type == DialogType::OpenFile {
+          return self.apply_command(Command::Open(entry));
+        }
+
+        // Files in folder and save dialogs and paths that don't exist go to the nearest folder.
+        let Some(parent) = path
+          .ancestors()
+          .skip(1)
+          .find(|ancestor| self.file_system.is_dir(ancestor))
+          .map(Path::to_path_buf)
+        else {
+          return;
+        };
+        let typed_name = path.parent() == Some(parent.as_path());
+        self.set_path(parent);
+        if self.dialog_type == DialogType::SaveFile {
+          if entry.is_file() {
+            self.reveal(path);
+          } else if let Some(name) = path.file_name().filter(|_| typed_name) {
+            self.filename_edit = name.to_string_lossy().to_string();
+          }
+        }
+      }
+      Command::BrowseDirectory(dir) => {
+        self.selected_file = Some(dir);
+        self.open_selected();
+      }
+      Command::Save(file) => {
+        if let Some(error) = self.save_error(&file.path) {
+          self.last_error = Some(error);
+          return;
+        }
+
+        // Check now rather than when the file was selected, it might have been created since.
+        let exists = self.file_system.exists(&file.path);
+        if exists && self.unique_on_conflict {
+          let path = self.unique_path(&file.path);
+          self.filename_edit = path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+          self.selected_file = Some(self.file_system.entry(&path));
+          self.confirm();
+        } else if exists && self.confirm_overwrite {
+          self.prompt = Some(Prompt::Overwrite(file.path));
+        } else {
+          self.selected_file = Some(file);
+          self.confirm();
+        }
+      }
+      Command::SaveTyped => {
+        let path = self.path.join(self.filename());
+        let command = match self.file_system.is_dir(&path) {
+          true => Command::Open(self.file_system.entry(&path)),
+          false => Command::Save(self.file_system.entry(&self.save_path())),
+        };
+        self.apply_command(command);
+      }
+      Command::Cancel => self.cancel(),
+      Command::Refresh => {
+        self.invalidate_cache(&self.path.clone());
+        self.refresh();
+      }
+      Command::UpDirectory => {
+        if self.path.pop() {
+          self.search_query.clear();
+          self.refresh();
+        }
+      }
+      Command::GoBack => {
+        // Only folders that could be read are recent.
+        match self.recent.iter().find(|path| **path != self.path) {
+          Some(path) => self.set_path(path.clone()),
+          None => self.apply_command(Command::UpDirectory),
+        }
+      }
+      Command::NewFolder => {
+        let mut name = self.labels.new_folder_name.to_string();
+        let mut number = 1;
+        while self.file_system.exists(&self.path.join(&name)) {
+          number += 1;
+          name = format!("{} {number}", self.labels.new_folder_name);
+        }
+        self.prompt = Some(Prompt::NewFolder(name));
+      }
+      Command::CreateDirectory(path) => match self.file_system.create_dir(&path) {
+        Ok(_) => {
+          self.notify(self.labels.folder_created, Level::Info);
+          self.emit_fs_event(FsEvent::Created(path.clone()));
+          self.refresh();
+          self.reveal(path);
+        }
+        Err(err) => self.fs_error(FsOp::CreateDirectory, self.labels.create_folder_error, err),
+      },
+      Command::Rename(from, to) => match self.file_system.rename(&from, &to) {
+        Ok(_) => {
+          self.notify(self.labels.renamed, Level::Info);
+          self.emit_fs_event(FsEvent::Renamed {
+            from,
+            to: to.clone(),
+          });
+          self.refresh();
+          self.reveal(to);
+        }
+        Err(err) => self.fs_error(FsOp::Rename, self.labels.rename_error, err),
+      },
+    };
+  }
+
+  /// Full path of an entry with its size, modification time and link target. The metadata is only
+  /// shown if it has already been read, so hovering doesn't touch the disk.
+  fn hover_text(&self, info: &FileInfo) -> String {
+    let mut text = self.display_path(&info.path);
+    let (size, modified) = match (info.stat, info.metadata.get()) {
+      (Some(stat), _) => (Some(stat.size), stat.modified),
+      (None, Some(Some(meta))) => (Some(meta.len()), meta.modified().ok()),
+      (None, _) => (None, None),
+    };
+
+    if let Some(size) = size.filter(|_| !info.is_dir()) {
+      let _ = write!(text, "\n{}", format_size(size));
+    }
+    if let Some(modified) = modified {
+      let _ = write!(text, "\n{}", format_time(modified, &self.date_items));
+    }
+    if let Some(link) = link_text(info, &self.labels) {
+      let _ = write!(text, "\n{link}");
+    }
+    text
+  }
+
+  /// Text of an entry in the list, with its icon if icons are shown.
+  fn entry_label(&self, icon: &str, name: &str) -> String {
+    match self.show_icons {
+      true => format!("{icon} {name}"),
+      false => name.to_string(),
+    }
+  }
+
+  /// Name of an entry, with a lock after it if it is read-only and icons are shown.
+  fn entry_name(&self, info: &FileInfo) -> String {
+    match self.show_icons && info.is_read_only() {
+      true => format!("{} {}", get_file_name(info), self.icons.read_only),
+      false => get_file_name(info).to_string(),
+    }
+  }
+
+  /// The `icon` of a button, or its `text` if icons are turned off.
+  fn icon_or_text(&self, icon: &'static str, text: &'static str) -> &'static str {
+    match self.show_icons {
+      true => icon,
+      false => text,
+    }
+  }
+
+  /// Title of the window, with the emoji for the dialog type if enabled.
+  fn window_title(&self) -> String {
+    let icon = match self.dialog_type {
+      DialogType::SelectFolder => self.icons.select_folder,
+      DialogType::OpenFile => self.icons.open_file,
+      DialogType::SaveFile => self.icons.save_file,
+    };
+    match self.title_icon && self.show_icons {
+      true => format!("{icon}  {}", self.title),
+      false => self.title.clone(),
+    }
+  }
+
+  /// Shows a message that disappears after a few seconds.
+  pub fn notify(&mut self, text: impl Into<String>, level: Level) {
+    self.toasts.push(Toast {
+      text: text.into(),
+      level,
+      shown_at: None,
+    });
+  }
+
+  /// Toasts at the bottom right corner of `rect`, fading out before they are removed.
+  fn toasts_ui(&mut self, ui: &mut Ui, rect: egui::Rect) {
+    if self.toasts.is_empty() {
+      return;
+    }
 
-        if ui.button(self.cancel_button_text).clicked() {
-          command = Some(Command::Cancel);
+    let time = ui.input(|state| state.time);
+    self.toasts.retain_mut(|toast| {
+      let shown_at = *toast.shown_at.get_or_insert(time);
+      time - shown_at < TOAST_DURATION
+    });
+
+    egui::Area::new(ui.id().with("egui_file_toasts"))
+      .order(egui::Order::Foreground)
+      .pivot(Align2::RIGHT_BOTTOM)
+      .fixed_pos(rect.right_bottom() - egui::vec2(8.0, 48.0))
+      .show(ui.ctx(), |ui| {
+        for toast in &self.toasts {
+          let remaining = TOAST_DURATION - (time - toast.shown_at.unwrap_or(time));
+          let color = match toast.level {
+            Level::Info => ui.visuals().text_color(),
+            Level::Warning => ui.visuals().warn_fg_color,
+            Level::Error => ui.visuals().error_fg_color,
+          };
+          ui.set_opacity(remaining.clamp(0.0, 1.0) as f32);
+          egui::Frame::popup(ui.style()).show(ui, |ui| {
+            ui.colored_label(color, &toast.text);
+          });
         }
+      });
 
-        #[cfg(unix)]
-        ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
-          if ui
-            .checkbox(&mut self.show_hidden, self.show_hidden_checkbox_text)
-            .changed()
-          {
-            self.refresh();
-          }
-        });
+    ui.ctx().request_repaint();
+  }
+
+  fn display_path(&self, path: &Path) -> String {
+    let text = path.to_str().unwrap_or_default();
+    match self.display_separator {
+      path::MAIN_SEPARATOR => text.to_string(),
+      separator => text.replace(path::MAIN_SEPARATOR, &separator.to_string()),
+    }
+  }
+
+  /// Folders in the folder that `text` ends in whose name starts with the rest of `text`.
+  fn path_suggestions(&self, text: &str) -> Vec<PathBuf> {
+    let Some(idx) = text.rfind(self.display_separator) else {
+      return Vec::new();
+    };
+    let (folder, prefix) = text.split_at(idx + self.display_separator.len_utf8());
+    let Ok(entries) = self.file_system.read_dir(&self.parse_path(folder), true) else {
+      return Vec::new();
+    };
+
+    let mut suggestions: Vec<PathBuf> = entries
+      .into_iter()
+      .filter(|info| {
+        let name = get_file_name(info);
+        // Hidden folders are only suggested once a dot is typed.
+        info.is_dir()
+          && name.starts_with(prefix)
+          && (prefix.starts_with('.') || !name.starts_with('.'))
+      })
+      .map(|info| info.path)
+      .collect();
+    suggestions.sort();
+    suggestions
+  }
+
+  fn parse_path(&self, text: &str) -> PathBuf {
+    // Accept both the display and the native separator.
+    match self.display_separator {
+      path::MAIN_SEPARATOR => PathBuf::from(text),
+      separator => PathBuf::from(text.replace(separator, path::MAIN_SEPARATOR_STR)),
+    }
+  }
+
+  /// Summary of the multi selection with buttons to proceed or go back to the selection.
+  fn multi_summary_ui(&mut self, ui: &mut Ui) {
+    let selection: Vec<&FileInfo> = self
+      .files
+      .iter()
+      .flatten()
+      .filter(|info| info.selected)
+      .collect();
+    let size: u64 = selection.iter().map(|info| info.size()).sum();
+
+    ui.label(fill(
+      self.labels.selected_size,
+      &[("count", &selection.len()), ("size", &format_size(size))],
+    ));
+    ui.separator();
+
+    let (mut proceed, mut back) = (false, false);
+    egui::TopBottomPanel::bottom("egui_file_summary_buttons").show_inside(ui, |ui| {
+      ui.horizontal(|ui| {
+        proceed = ui.button(self.labels.proceed_button).clicked();
+        back = ui.button(self.labels.back_button).clicked();
       });
     });
 
-    // File list.
-    egui::CentralPanel::default().show_inside(ui, |ui| {
-      ScrollArea::vertical().show_rows(
-        ui,
-        ui.text_style_height(&egui::TextStyle::Body),
-        self.files.as_ref().map_or(0, |files| files.len()),
-        |ui, range| match self.files.as_ref() {
-          Ok(files) => {
-            ui.with_layout(ui.layout().with_cross_justify(true), |ui| {
-              let selected = self.selected_file.as_ref().map(|info| &info.path);
-              let range_start = range.start;
-
-              for (n, info) in files[range].iter().enumerate() {
-                let idx = n + range_start;
-                let label = match info.is_dir() {
-                  true => "🗀 ",
-                  false => "🗋 ",
-                }
-                .to_string()
-                  + get_file_name(info);
+    ScrollArea::vertical().show(ui, |ui| {
+      for info in &selection {
+        let name = get_file_name(info);
+        match info.metadata() {
+          Some(meta) if info.is_file() => ui.label(format!("{name} ({})", format_size(meta.len()))),
+          _ => ui.label(name),
+        };
+      }
+    });
 
-                let is_selected = if self.multi_select_enabled {
-                  files[idx].selected
-                } else {
-                  Some(&info.path) == selected
-                };
-                let response = ui.selectable_label(is_selected, label);
-                if response.clicked() {
-                  if self.multi_select_enabled {
-                    if ui.input(|i| i.modifiers.shift) {
-                      command = Some(Command::MultiSelectRange(idx))
-                    } else if ui.input(|i| i.modifiers.ctrl) {
-                      command = Some(Command::MultiSelectSwitch(idx))
-                    } else {
-                      command = Some(Command::MultiSelect(idx))
-                    }
-                  } else {
-                    command = Some(Command::Select(info.clone()));
-                  }
-                }
+    if proceed || back {
+      self.show_multi_summary = false;
+    }
 
-                if response.double_clicked() {
-                  match self.dialog_type {
-                    DialogType::SelectFolder => {
-                      // Always open folder on double click, otherwise SelectFolder cant enter sub-folders.
-                      command = Some(Command::OpenSelected);
-                    }
-                    // Open or save file only if name matches filter.
-                    DialogType::OpenFile => {
-                      if info.is_dir() {
-                        command = Some(Command::BrowseDirectory(info.clone()));
-                      } else if (self.filename_filter)(self.filename_edit.as_str()) {
-                        command = Some(Command::Open(info.clone()));
-                      }
-                    }
-                    DialogType::SaveFile => {
-                      if info.is_dir() {
-                        command = Some(Command::OpenSelected);
-                      } else if (self.filename_filter)(self.filename_edit.as_str()) {
-                        command = Some(Command::Save(info.clone()));
-                      }
-                    }
-                  }
-                }
-              }
-            })
-            .response
-          }
-          Err(e) => ui.label(e.to_string()),
-        },
-      );
-    });
+    if proceed {
+      self.confirm();
+    }
+  }
 
-    if let Some(command) = command {
-      match command {
-        Command::Select(info) => self.select(Some(info)),
-        Command::MultiSelect(idx) => self.select_reset_multi(idx),
-        Command::MultiSelectRange(idx) => self.select_range(idx),
-        Command::MultiSelectSwitch(idx) => self.select_switch_multi(idx),
-        Command::Folder => {
-          let path = self.get_folder().to_owned();
-          self.selected_file = Some(FileInfo::new(path));
-          self.confirm();
-        }
-        Command::Open(path) => {
-          self.select(Some(path));
-          self.open_selected();
-        }
-        Command::OpenSelected => self.open_selected(),
-        Command::BrowseDirectory(dir) => {
-          self.selected_file = Some(dir);
-          self.open_selected();
-        }
-        Command::Save(file) => {
-          self.selected_file = Some(file);
-          self.confirm();
-        }
-        Command::Cancel => self.state = State::Cancelled,
-        Command::Refresh => self.refresh(),
-        Command::UpDirectory => {
-          if self.path.pop() {
-            self.refresh();
-          }
-        }
-        Command::CreateDirectory => {
-          let mut path = self.path.clone();
-          let name = match self.filename_edit.is_empty() {
-            true => self.new_folder_name_text,
-            false => &self.filename_edit,
-          };
-          path.push(name);
-          match fs::create_dir(&path) {
-            Ok(_) => {
-              self.refresh();
-              self.select(Some(FileInfo::new(path)));
-              // TODO: scroll to selected?
-            }
-            Err(err) => println!("Error while creating directory: {err}"),
-          }
-        }
-        Command::Rename(from, to) => match fs::rename(from, &to) {
-          Ok(_) => {
-            self.refresh();
-            self.select(Some(FileInfo::new(to)));
-          }
-          Err(err) => println!("Error while renaming: {err}"),
-        },
-      };
+  fn emit_fs_event(&mut self, event: FsEvent) {
+    // The listings of the changed directories are outdated.
+    let changed = match &event {
+      FsEvent::Created(path) | FsEvent::Deleted(path) => vec![path.parent()],
+      FsEvent::Renamed { from, to } => vec![from.parent(), to.parent()],
+      FsEvent::Error { .. } => Vec::new(),
+    };
+    for dir in changed.into_iter().flatten() {
+      self.invalidate_cache(dir);
+    }
+
+    if let Some(handler) = &mut self.fs_event_handler {
+      handler(event);
     }
   }
 
+  /// Show the error of a failed operation in the dialog, filled into `template`, and report it to
+  /// the event handler.
+  fn fs_error(&mut self, op: FsOp, template: &str, error: Error) {
+    self.last_error = Some(fill(template, &[("error", &error)]));
+    self.emit_fs_event(FsEvent::Error { op, error });
+  }
+
+  /// Error text for `path` that couldn't be opened.
+  fn open_error(&self, path: &Path, error: &Error) -> String {
+    let values: [(&str, &dyn Display); 2] = [("path", &path.display()), ("error", error)];
+    fill(self.labels.open_error, &values)
+  }
+
   fn get_folder(&self) -> &Path {
     if let Some(info) = &self.selected_file {
       if info.is_dir() {
@@ -915,14 +5687,14 @@ impl FileDialog {
     &self.path
   }
 
-  fn read_folder(&self) -> Result<Vec<FileInfo>, Error> {
-    fs::read_dir(&self.path).map(|entries| {
+  fn read_folder(&self, entries: Result<Vec<FileInfo>, Error>) -> Result<Vec<FileInfo>, Error> {
+    entries.map(|entries| {
       let mut file_infos: Vec<FileInfo> = entries
-        .filter_map(|result| result.ok())
-        .filter_map(|entry| {
-          let info = FileInfo::new(entry.path());
+        .into_iter()
+        .filter_map(|mut info| {
           if !info.is_dir() {
-            if !self.show_system_files && !info.path.is_file() {
+            // Broken links are listed, though they are neither files nor folders.
+            if !self.show_system_files && !info.is_file() && !info.is_symlink() {
               // Do not show system files.
               return None;
             }
@@ -931,23 +5703,24 @@ impl FileDialog {
             if !(self.show_files_filter)(&info.path) {
               return None;
             }
+          } else if !(self.show_dirs_filter)(&info.path) {
+            return None;
           }
 
-          #[cfg(unix)]
-          if !self.show_hidden && get_file_name(&info).starts_with('.') {
+          if !self.show_hidden && is_hidden(&info) {
             return None;
           }
 
+          // Sort links by their own size and modification time.
+          if let Some(metadata) = info.link_metadata.take().filter(|_| !self.follow_symlinks) {
+            info.metadata = OnceCell::from(Some(metadata));
+          }
+
           Some(info)
         })
         .collect();
 
-      // Sort with folders before files.
-      file_infos.sort_by(|a, b| match b.is_dir().cmp(&a.is_dir()) {
-        Ordering::Less => Ordering::Less,
-        Ordering::Equal => a.path.file_name().cmp(&b.path.file_name()),
-        Ordering::Greater => Ordering::Greater,
-      });
+      file_infos.sort_by(|a, b| compare_files(a, b, self.sort_by, self.sort_reverse));
 
       #[cfg(windows)]
       let file_infos = match self.show_drives {
@@ -955,7 +5728,7 @@ impl FileDialog {
           let drives = get_drives();
           let mut infos = Vec::with_capacity(drives.len() + file_infos.len());
           for drive in drives {
-            infos.push(FileInfo::new(drive));
+            infos.push(self.file_system.entry(&drive));
           }
           infos.append(&mut file_infos);
           infos
@@ -968,29 +5741,262 @@ impl FileDialog {
   }
 }
 
+#[derive(Debug)]
+enum Prompt {
+  /// Saving over an existing file.
+  Overwrite(PathBuf),
+  /// Deleting a file or folder.
+  Delete(PathBuf),
+  /// Deleting a folder with the number of items directly in it.
+  DeleteFolder(PathBuf, usize),
+  /// Deleting the entries of a multi selection.
+  DeleteSelection(Vec<PathBuf>),
+  /// Cancelling with a typed file name.
+  Discard,
+  /// Entering the name of a new folder.
+  NewFolder(String),
+}
+
+#[doc(hidden)]
+#[non_exhaustive]
+/// Action of the user, carried out with [`FileDialog::apply_command`]. Indices refer to
+/// [`FileDialog::current_entries`]. Only public for tests, it isn't covered by semver.
+pub enum Command {
+  /// Cancel the dialog.
+  Cancel,
+  /// Answer the pending question, yes carries out its action.
+  AnswerPrompt(bool),
+  /// Show only entries whose name contains the text, ignoring case.
+  Search(String),
+  /// Ask for the name of a new folder in the current folder.
+  NewFolder,
+  /// Create a folder and select it.
+  CreateDirectory(PathBuf),
+  /// Choose the selected folder, or the current one, in folder selection dialogs.
+  Folder,
+  /// Browse into a folder, or choose a file in dialogs that open files.
+  Open(FileInfo),
+  /// Open the selected entry, or confirm the multi selection.
+  OpenSelected,
+  /// Go to a path typed into the path field, depending on the dialog type.
+  EnterPath(PathBuf),
+  /// Browse into a folder.
+  BrowseDirectory(FileInfo),
+  /// Read the current folder again.
+  Refresh,
+  /// Rename a file or folder.
+  Rename(PathBuf, PathBuf),
+  /// Choose a file in save dialogs, asking before overwriting it if enabled.
+  Save(FileInfo),
+  /// Save under the name typed into the file name field, or browse into it if it is a folder.
+  SaveTyped,
+  /// Select an entry in single-select mode.
+  Select(FileInfo),
+  /// Select the entries from the last selected one to the entry at the index.
+  MultiSelectRange(usize),
+  /// Select only the entry at the index.
+  MultiSelect(usize),
+  /// Add the entry at the index to the selection or remove it.
+  MultiSelectSwitch(usize),
+  /// Select all files in multi-select mode.
+  SelectAll,
+  /// Clear the multi selection.
+  ClearSelection,
+  /// Go to the parent folder.
+  UpDirectory,
+  /// Go back to the last folder that could be read, or else to the parent folder.
+  GoBack,
+  /// Rename an entry in its row of the file list, or in the file name field in the grid view.
+  StartRename(FileInfo),
+  /// Ask whether to delete a file or folder.
+  Delete(PathBuf),
+  /// Ask whether to delete the entries of the multi selection.
+  DeleteSelection,
+  /// Show an entry in the system file manager.
+  #[cfg(feature = "open")]
+  ShowInFileManager(PathBuf),
+  /// Open a file with the default application.
+  #[cfg(feature = "open")]
+  OpenWithDefaultApp(PathBuf),
+}
+
 #[derive(Clone, Debug, Default)]
-struct FileInfo {
+/// Entry of the listed directory.
+pub struct FileInfo {
   path: PathBuf,
   file_type: Option<FileType>,
+  metadata: OnceCell<Option<Metadata>>,
+  /// Set for entries of a custom file system, instead of the file type and metadata.
+  stat: Option<Stat>,
+  is_symlink: bool,
+
+  /// Path the symbolic link points to.
+  link_target: Option<PathBuf>,
+
+  /// Metadata of the symbolic link itself, rather than of its target.
+  link_metadata: Option<Metadata>,
   selected: bool,
+
+  /// Modification time as it is shown, with the minute it was formatted in for relative times.
+  time_text: RefCell<Option<(u64, String)>>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Stat {
+  is_dir: bool,
+  size: u64,
+  modified: Option<SystemTime>,
 }
 
 impl FileInfo {
   fn new(path: PathBuf) -> Self {
-    let file_type = fs::metadata(&path).ok().map(|meta| meta.file_type());
+    let metadata = fs::metadata(&path).ok();
+    let link_metadata = fs::symlink_metadata(&path)
+      .ok()
+      .filter(|meta| meta.file_type().is_symlink());
+    let is_symlink = link_metadata.is_some();
+    Self {
+      file_type: metadata.as_ref().map(|meta| meta.file_type()),
+      metadata: OnceCell::from(metadata),
+      stat: None,
+      is_symlink,
+      link_target: is_symlink.then(|| fs::read_link(&path).ok()).flatten(),
+      link_metadata,
+      path,
+      selected: false,
+      time_text: RefCell::new(None),
+    }
+  }
+
+  /// Create a folder entry for a custom [`FileSystem`].
+  pub fn virtual_dir(path: impl Into<PathBuf>) -> Self {
+    Self::with_stat(path.into(), true, 0, None)
+  }
+
+  /// Create a file entry for a custom [`FileSystem`].
+  pub fn virtual_file(path: impl Into<PathBuf>, size: u64, modified: Option<SystemTime>) -> Self {
+    Self::with_stat(path.into(), false, size, modified)
+  }
+
+  fn with_stat(path: PathBuf, is_dir: bool, size: u64, modified: Option<SystemTime>) -> Self {
+    Self {
+      path,
+      file_type: None,
+      metadata: OnceCell::from(None),
+      stat: Some(Stat {
+        is_dir,
+        size,
+        modified,
+      }),
+      is_symlink: false,
+      link_target: None,
+      link_metadata: None,
+      selected: false,
+      time_text: RefCell::new(None),
+    }
+  }
+
+  /// Create from a directory entry. With `lazy_metadata`, the metadata is only read once it is
+  /// needed, and the file type comes from the directory listing where possible.
+  fn from_entry(entry: &DirEntry, lazy_metadata: bool) -> Self {
+    let path = entry.path();
+    let file_type = match entry.file_type() {
+      // Symbolic links need to be resolved to tell folders apart.
+      Ok(file_type) if lazy_metadata && !file_type.is_symlink() => Some(file_type),
+      _ => return Self::new(path),
+    };
+
     Self {
       path,
       file_type,
+      metadata: OnceCell::new(),
+      stat: None,
+      is_symlink: false,
+      link_target: None,
+      link_metadata: None,
       selected: false,
+      time_text: RefCell::new(None),
+    }
+  }
+
+  /// Metadata of the entry, read on first access. `None` if it can't be read or the entry is
+  /// from a custom [`FileSystem`].
+  pub fn metadata(&self) -> Option<&Metadata> {
+    // Only entries listed by `StdFileSystem` have a file type without their metadata, the ones
+    // of a custom file system are never read from the disk.
+    self
+      .metadata
+      .get_or_init(|| match self.file_type {
+        Some(_) => fs::metadata(&self.path).ok(),
+        None => None,
+      })
+      .as_ref()
+  }
+
+  /// Path of the entry.
+  pub fn path(&self) -> &Path {
+    &self.path
+  }
+
+  /// Returns true, if the entry is a file.
+  pub fn is_file(&self) -> bool {
+    match self.stat {
+      Some(stat) => !stat.is_dir,
+      None => self.file_type.is_some_and(|file_type| file_type.is_file()),
+    }
+  }
+
+  /// Returns true, if the entry is a directory.
+  pub fn is_dir(&self) -> bool {
+    match self.stat {
+      Some(stat) => stat.is_dir,
+      None => self.file_type.is_some_and(|file_type| file_type.is_dir()),
+    }
+  }
+
+  /// Returns true, if the entry is a symbolic link. Its other properties are the ones of the
+  /// target.
+  pub fn is_symlink(&self) -> bool {
+    self.is_symlink
+  }
+
+  /// Path the symbolic link points to, `None` if the entry is not a link.
+  pub fn link_target(&self) -> Option<&Path> {
+    self.link_target.as_deref()
+  }
+
+  /// Returns true, if the entry is a symbolic link whose target doesn't exist.
+  pub fn is_broken_link(&self) -> bool {
+    self.is_symlink && self.file_type.is_none()
+  }
+
+  /// Size of the file in bytes, `0` for folders or if the metadata can't be read.
+  pub fn size(&self) -> u64 {
+    match (self.is_dir(), self.stat) {
+      (true, _) => 0,
+      (false, Some(stat)) => stat.size,
+      (false, None) => self.metadata().map_or(0, |meta| meta.len()),
+    }
+  }
+
+  /// Last modification time, `None` if it can't be read.
+  pub fn modified(&self) -> Option<SystemTime> {
+    match self.stat {
+      Some(stat) => stat.modified,
+      None => self.metadata().and_then(|meta| meta.modified().ok()),
     }
   }
 
-  fn is_file(&self) -> bool {
-    self.file_type.is_some_and(|file_type| file_type.is_file())
+  /// Returns true, if the entry can't be written to according to its permissions.
+  pub fn is_read_only(&self) -> bool {
+    self
+      .metadata()
+      .is_some_and(|metadata| metadata.permissions().readonly())
   }
 
-  fn is_dir(&self) -> bool {
-    self.file_type.is_some_and(|file_type| file_type.is_dir())
+  /// Returns true, if the entry is part of the multi selection.
+  pub fn is_selected(&self) -> bool {
+    self.selected
   }
 }
 
@@ -1009,6 +6015,153 @@ fn get_drives() -> Vec<PathBuf> {
   drive_names
 }
 
+/// Open the system file manager at `path`, with the file selected where that is supported.
+#[cfg(feature = "open")]
+fn show_in_file_manager(path: &Path) -> Result<(), Error> {
+  #[cfg(windows)]
+  if path.is_file() {
+    let mut arg = std::ffi::OsString::from("/select,");
+    arg.push(path);
+    return std::process::Command::new("explorer")
+      .arg(arg)
+      .spawn()
+      .map(|_| ());
+  }
+
+  #[cfg(target_os = "macos")]
+  if path.is_file() {
+    return std::process::Command::new("open")
+      .arg("-R")
+      .arg(path)
+      .spawn()
+      .map(|_| ());
+  }
+
+  // Other file managers can't select a file, open its folder instead.
+  let folder = match path.is_dir() {
+    true => path,
+    false => path.parent().unwrap_or(path),
+  };
+  open::that_detached(folder)
+}
+
+#[cfg(feature = "zip")]
+fn is_zip(path: &Path) -> bool {
+  path
+    .extension()
+    .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"))
+}
+
+/// Volumes mounted under `/media`, `/mnt` and `/Volumes`. Udisks mounts removable media under
+/// `/media/<user>`, so that folder is listed instead of itself.
+#[cfg(unix)]
+fn get_mounts() -> Vec<PathBuf> {
+  let user = env::var_os("USER");
+  let mut mounts = Vec::new();
+  for root in ["/media", "/mnt", "/Volumes"] {
+    let Ok(entries) = fs::read_dir(root) else {
+      continue;
+    };
+    for entry in entries.filter_map(|result| result.ok()) {
+      let path = entry.path();
+      if !path.is_dir() {
+        continue;
+      }
+
+      if root == "/media" && Some(entry.file_name()) == user {
+        if let Ok(entries) = fs::read_dir(&path) {
+          let entries = entries.filter_map(|result| result.ok());
+          mounts.extend(
+            entries
+              .map(|entry| entry.path())
+              .filter(|path| path.is_dir()),
+          );
+        }
+        continue;
+      }
+
+      mounts.push(path);
+    }
+  }
+  mounts.sort();
+  mounts
+}
+
+/// The user's home, desktop, documents and downloads directories that exist.
+fn get_quick_access(labels: &FileDialogLabels) -> Vec<(String, PathBuf)> {
+  [
+    (labels.home, dirs::home_dir()),
+    (labels.desktop, dirs::desktop_dir()),
+    (labels.documents, dirs::document_dir()),
+    (labels.downloads, dirs::download_dir()),
+  ]
+  .into_iter()
+  .filter_map(|(name, path)| Some((name.to_string(), path?)))
+  .filter(|(_, path)| path.is_dir())
+  .collect()
+}
+
+#[cfg(windows)]
+fn get_known_folders() -> Vec<(&'static str, PathBuf)> {
+  use std::{ffi::OsString, os::windows::ffi::OsStringExt, ptr};
+
+  const FOLDERS: [(&str, Guid); 4] = [
+    (
+      "AppData",
+      Guid(
+        0x3EB685DB,
+        0x65F9,
+        0x4CF6,
+        [0xA0, 0x3A, 0xE3, 0xEF, 0x65, 0x72, 0x9F, 0x3D],
+      ),
+    ),
+    (
+      "Local AppData",
+      Guid(
+        0xF1B32785,
+        0x6FBA,
+        0x4FCF,
+        [0x9D, 0x55, 0x7B, 0x8E, 0x7F, 0x15, 0x70, 0x91],
+      ),
+    ),
+    (
+      "LocalLow AppData",
+      Guid(
+        0xA520A1A4,
+        0x1780,
+        0x4FF6,
+        [0xBD, 0x18, 0x16, 0x73, 0x43, 0xC5, 0xAF, 0x16],
+      ),
+    ),
+    (
+      "ProgramData",
+      Guid(
+        0x62AB5D82,
+        0xFDC1,
+        0x4DC3,
+        [0xA9, 0xDD, 0x07, 0x0D, 0x1D, 0x49, 0x5D, 0x97],
+      ),
+    ),
+  ];
+
+  let mut folders = Vec::with_capacity(FOLDERS.len());
+  for (name, guid) in &FOLDERS {
+    let mut buf: *mut u16 = ptr::null_mut();
+    let result = unsafe { SHGetKnownFolderPath(guid, 0, ptr::null_mut(), &mut buf) };
+    if result == 0 && !buf.is_null() {
+      let len = (0..).take_while(|&i| unsafe { *buf.add(i) } != 0).count();
+      let path = OsString::from_wide(unsafe { std::slice::from_raw_parts(buf, len) });
+      folders.push((*name, PathBuf::from(path)));
+    }
+
+    // The buffer must be freed even if the call failed.
+    unsafe { CoTaskMemFree(buf.cast()) };
+  }
+
+  folders.retain(|(_, path)| path.is_dir());
+  folders
+}
+
 #[cfg(windows)]
 fn is_drive_root(path: &Path) -> bool {
   path
@@ -1018,6 +6171,343 @@ fn is_drive_root(path: &Path) -> bool {
     .map_or(false, |ch| ch.is_ascii_uppercase())
 }
 
+/// Read up to `max_bytes` from the start of a text file. `None` if the file isn't valid UTF-8.
+fn read_text_preview(path: &Path, max_bytes: usize) -> Result<Option<String>, Error> {
+  let mut bytes = Vec::new();
+  let file = fs::File::open(path)?;
+  file.take(max_bytes as u64).read_to_end(&mut bytes)?;
+
+  if bytes.contains(&0) {
+    return Ok(None);
+  }
+
+  Ok(match String::from_utf8(bytes) {
+    Ok(text) => Some(text),
+    // The limit might split the last character.
+    Err(err) if err.utf8_error().error_len().is_none() => {
+      let valid = err.utf8_error().valid_up_to();
+      let mut bytes = err.into_bytes();
+      bytes.truncate(valid);
+      String::from_utf8(bytes).ok()
+    }
+    Err(_) => None,
+  })
+}
+
+/// Returns true, if the file is an image that can be decoded.
+#[cfg(feature = "image")]
+fn is_image(path: &Path) -> bool {
+  image::ImageFormat::from_path(path).is_ok_and(|format| format.reading_enabled())
+}
+
+/// Decode the image at `path` and scale it down to fit into `size`.
+#[cfg(feature = "image")]
+fn load_thumbnail(ctx: &Context, path: &Path, size: Vec2) -> Option<egui::TextureHandle> {
+  let image = image::open(path).ok()?;
+  let image = image.thumbnail(size.x as u32, size.y as u32).to_rgba8();
+  let size = [image.width() as usize, image.height() as usize];
+  let image = egui::ColorImage::from_rgba_unmultiplied(size, image.as_raw());
+  let name = path.to_string_lossy();
+  Some(ctx.load_texture(name, image, egui::TextureOptions::default()))
+}
+
+/// Title of a dialog of `dialog_type` in the language of `labels`.
+fn default_title(dialog_type: DialogType, labels: &FileDialogLabels) -> &'static str {
+  match dialog_type {
+    DialogType::SelectFolder => labels.select_folder_title,
+    DialogType::OpenFile => labels.open_file_title,
+    DialogType::SaveFile => labels.save_file_title,
+  }
+}
+
+/// Permission bits of a unix `mode` in the form `rwxr-xr-x`.
+#[cfg(unix)]
+fn format_permissions(mode: u32) -> String {
+  let mut text = String::with_capacity(9);
+  for shift in [6, 3, 0] {
+    let bits = mode >> shift;
+    text.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+    text.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+    text.push(if bits & 0o1 != 0 { 'x' } else { '-' });
+  }
+  text
+}
+
+/// The folder to open for the initial `path` and the name of the file in it, if `path` is a file.
+/// A path that doesn't exist is taken as a file if it has an extension, so that its name is kept.
+fn split_initial_path(
+  file_system: &dyn FileSystem,
+  dialog_type: DialogType,
+  path: &Path,
+) -> (PathBuf, String) {
+  let info = file_system.entry(path);
+  let missing_file = dialog_type != DialogType::SelectFolder
+    && !file_system.exists(path)
+    && path.extension().is_some();
+  if info.is_file() || missing_file {
+    assert!(dialog_type != DialogType::SelectFolder);
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let folder = path.parent().unwrap_or(path).to_path_buf();
+    return (folder, name.to_string());
+  }
+  (path.to_path_buf(), String::new())
+}
+
+/// The absolute path of `path` with links resolved, without the `\\?\` prefix that Windows adds
+/// where it isn't needed.
+fn canonicalize(path: &Path) -> Result<PathBuf, Error> {
+  let path = fs::canonicalize(path)?;
+
+  #[cfg(windows)]
+  if let Some(text) = path.to_str() {
+    if let Some(rest) = text.strip_prefix(r"\\?\UNC\") {
+      return Ok(PathBuf::from(format!(r"\\{rest}")));
+    }
+    if let Some(path::Component::Prefix(prefix)) = path.components().next() {
+      if let path::Prefix::VerbatimDisk(_) = prefix.kind() {
+        return Ok(PathBuf::from(&text[4..]));
+      }
+    }
+  }
+
+  Ok(path)
+}
+
+/// Returns true, if the name of the entry starts with a dot or, on Windows, it has the hidden or
+/// system attribute.
+fn is_hidden(info: &FileInfo) -> bool {
+  #[cfg(windows)]
+  {
+    use std::os::windows::fs::MetadataExt;
+
+    const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+    const FILE_ATTRIBUTE_SYSTEM: u32 = 0x4;
+    let attributes = info.metadata().map_or(0, |meta| meta.file_attributes());
+    if attributes & (FILE_ATTRIBUTE_HIDDEN | FILE_ATTRIBUTE_SYSTEM) != 0 {
+      return true;
+    }
+  }
+
+  get_file_name(info).starts_with('.')
+}
+
+/// Returns true, if any of the executable bits of the file is set.
+#[cfg(unix)]
+fn is_executable(info: &FileInfo) -> bool {
+  use std::os::unix::fs::PermissionsExt;
+
+  info
+    .metadata()
+    .is_some_and(|meta| meta.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(_info: &FileInfo) -> bool {
+  false
+}
+
+/// Hover text with the target of a symbolic link.
+fn link_text(info: &FileInfo, labels: &FileDialogLabels) -> Option<String> {
+  let target = info.link_target()?.display();
+  let template = match info.is_broken_link() {
+    true => labels.broken_link_target,
+    false => labels.link_target,
+  };
+  Some(fill(template, &[("target", &target)]))
+}
+
+/// `template` with each `{key}` replaced by its value, for the templates of [`FileDialogLabels`].
+fn fill(template: &str, values: &[(&str, &dyn Display)]) -> String {
+  values
+    .iter()
+    .fold(template.to_string(), |text, (key, value)| {
+      text.replace(&format!("{{{key}}}"), &value.to_string())
+    })
+}
+
+/// Single line of `text` that is cut off with "…" at `width`.
+fn truncated_text(ui: &Ui, text: String, width: f32) -> egui::WidgetText {
+  // The placeholder color is replaced with the color of the widget.
+  let font = egui::TextStyle::Body.resolve(ui.style());
+  let mut job = egui::text::LayoutJob::simple_singleline(text, font, egui::Color32::PLACEHOLDER);
+  job.wrap = egui::text::TextWrapping::truncate_at_width(width.max(0.0));
+  job.into()
+}
+
+/// Paint a large icon in place of a thumbnail.
+fn paint_tile_icon(ui: &Ui, thumb_rect: egui::Rect, icon: &str) {
+  let font = egui::FontId::proportional(thumb_rect.height() * 0.6);
+  let color = ui.visuals().text_color();
+  ui.painter().text(
+    thumb_rect.center(),
+    Align2::CENTER_CENTER,
+    icon,
+    font,
+    color,
+  );
+}
+
+/// Paint the name of a grid tile below its thumbnail, shortened to fit on one line.
+fn paint_tile_name(ui: &Ui, rect: egui::Rect, thumb_rect: egui::Rect, name: &str) {
+  let mut job = egui::text::LayoutJob::simple_singleline(
+    name.to_string(),
+    egui::TextStyle::Body.resolve(ui.style()),
+    ui.visuals().text_color(),
+  );
+  job.wrap = egui::text::TextWrapping::truncate_at_width(rect.width() - 4.0);
+  let galley = ui.painter().layout_job(job);
+  let pos = egui::pos2(
+    rect.center().x - galley.size().x / 2.0,
+    thumb_rect.bottom() + 2.0,
+  );
+  ui.painter().galley(pos, galley, ui.visuals().text_color());
+}
+
+/// Paint the background of a grid tile.
+fn paint_tile(ui: &Ui, rect: egui::Rect, selected: bool, hovered: bool) {
+  let visuals = ui.visuals();
+  let fill = if selected {
+    visuals.selection.bg_fill
+  } else if hovered {
+    visuals.widgets.hovered.weak_bg_fill
+  } else {
+    return;
+  };
+  ui.painter()
+    .rect_filled(rect, visuals.widgets.hovered.rounding, fill);
+}
+
+/// Parse a strftime-style pattern, so that times can be formatted without parsing it each time.
+/// An invalid pattern results in no items.
+fn parse_date_format(format: &str) -> Vec<chrono::format::Item<'static>> {
+  chrono::format::StrftimeItems::new(format)
+    .parse_to_owned()
+    .unwrap_or_default()
+}
+
+/// Format a time in the local time zone with a parsed date format.
+fn format_time(time: SystemTime, items: &[chrono::format::Item<'static>]) -> String {
+  let mut text = String::new();
+  let time = chrono::DateTime::<chrono::Local>::from(time);
+  if write!(text, "{}", time.format_with_items(items.iter())).is_err() {
+    text.clear();
+  }
+  text
+}
+
+/// Format a time relative to `now`, e.g. "3 days ago". `None` if the time is more than four
+/// weeks ago.
+fn format_relative_time(
+  time: SystemTime,
+  now: SystemTime,
+  labels: &FileDialogLabels,
+) -> Option<String> {
+  const MINUTE: u64 = 60;
+  const HOUR: u64 = 60 * MINUTE;
+  const DAY: u64 = 24 * HOUR;
+  const WEEK: u64 = 7 * DAY;
+
+  // Times in the future are treated as now.
+  let secs = now
+    .duration_since(time)
+    .map_or(0, |elapsed| elapsed.as_secs());
+  let (count, one, many) = if secs < MINUTE {
+    return Some(labels.just_now.to_string());
+  } else if secs < HOUR {
+    (secs / MINUTE, labels.minute_ago, labels.minutes_ago)
+  } else if secs < DAY {
+    (secs / HOUR, labels.hour_ago, labels.hours_ago)
+  } else if secs < WEEK {
+    (secs / DAY, labels.day_ago, labels.days_ago)
+  } else if secs <= 4 * WEEK {
+    (secs / WEEK, labels.week_ago, labels.weeks_ago)
+  } else {
+    return None;
+  };
+
+  Some(match count {
+    1 => one.to_string(),
+    _ => fill(many, &[("count", &count)]),
+  })
+}
+
+/// Format a byte count with binary prefixes, e.g. "1.4 MiB".
+fn format_size(size: u64) -> String {
+  const UNITS: [&str; 6] = ["KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+  if size < 1024 {
+    return format!("{size} B");
+  }
+
+  let mut value = size as f64 / 1024.0;
+  let mut unit = 0;
+  while value >= 1024.0 && unit < UNITS.len() - 1 {
+    value /= 1024.0;
+    unit += 1;
+  }
+  format!("{value:.1} {}", UNITS[unit])
+}
+
+/// Order with folders before files, each sorted by `sort_by`.
+fn compare_files(a: &FileInfo, b: &FileInfo, sort_by: SortKey, reverse: bool) -> Ordering {
+  #[cfg(windows)]
+  match (is_drive_root(&a.path), is_drive_root(&b.path)) {
+    (true, true) => return a.path.cmp(&b.path),
+    (true, false) => return Ordering::Less,
+    (false, true) => return Ordering::Greater,
+    (false, false) => {}
+  }
+
+  match b.is_dir().cmp(&a.is_dir()) {
+    Ordering::Less => Ordering::Less,
+    Ordering::Equal => {
+      let by_name = || a.path.file_name().cmp(&b.path.file_name());
+      let ordering = match sort_by {
+        SortKey::Name => by_name(),
+        SortKey::Size => a.size().cmp(&b.size()).then_with(by_name),
+        SortKey::Modified => {
+          // Entries without a readable time are sorted as the oldest.
+          let a_time = a.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+          let b_time = b.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+          a_time.cmp(&b_time).then_with(by_name)
+        }
+      };
+      match reverse {
+        true => ordering.reverse(),
+        false => ordering,
+      }
+    }
+    Ordering::Greater => Ordering::Greater,
+  }
+}
+
+/// Put the cursor of the text field `id` after the end of its `text`.
+fn set_cursor_to_end(ctx: &Context, id: Id, text: &str) {
+  if let Some(mut state) = TextEdit::load_state(ctx, id) {
+    let end = egui::text::CCursor::new(text.chars().count());
+    state
+      .cursor
+      .set_char_range(Some(egui::text::CCursorRange::one(end)));
+    state.store(ctx, id);
+  }
+}
+
+/// Select the name of the text field `id` without the extension of its `text`, like file managers
+/// do when renaming.
+fn select_file_stem(ctx: &Context, id: Id, text: &str) {
+  if let Some(mut state) = TextEdit::load_state(ctx, id) {
+    let stem = match text.rfind('.') {
+      Some(0) | None => text,
+      Some(dot) => &text[..dot],
+    };
+    let range = egui::text::CCursorRange::two(
+      egui::text::CCursor::new(0),
+      egui::text::CCursor::new(stem.chars().count()),
+    );
+    state.cursor.set_char_range(Some(range));
+    state.store(ctx, id);
+  }
+}
+
 fn get_file_name(info: &FileInfo) -> &str {
   #[cfg(windows)]
   if info.is_dir() && is_drive_root(&info.path) {
@@ -1034,3 +6524,24 @@ fn get_file_name(info: &FileInfo) -> &str {
 extern "C" {
   pub fn GetLogicalDrives() -> u32;
 }
+
+#[cfg(windows)]
+#[repr(C)]
+struct Guid(u32, u16, u16, [u8; 8]);
+
+#[cfg(windows)]
+#[link(name = "shell32")]
+extern "system" {
+  fn SHGetKnownFolderPath(
+    rfid: *const Guid,
+    flags: u32,
+    token: *mut std::ffi::c_void,
+    path: *mut *mut u16,
+  ) -> i32;
+}
+
+#[cfg(windows)]
+#[link(name = "ole32")]
+extern "system" {
+  fn CoTaskMemFree(ptr: *mut std::ffi::c_void);
+}
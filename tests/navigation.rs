@@ -0,0 +1,689 @@
+use egui_file::{Command, FileDialog, FileInfo, FileSystem, State, StdFileSystem};
+use std::{
+  env, fs,
+  io::{Error, ErrorKind},
+  path::{Path, PathBuf},
+};
+
+/// Temporary folder with the given files, removed when dropped.
+struct TempDir(PathBuf);
+
+impl TempDir {
+  fn new(name: &str, files: &[&str]) -> Self {
+    let path = env::temp_dir().join(format!("egui_file_{name}_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&path);
+    fs::create_dir_all(&path).unwrap();
+    for file in files {
+      fs::write(path.join(file), b"").unwrap();
+    }
+    Self(path)
+  }
+}
+
+impl Drop for TempDir {
+  fn drop(&mut self) {
+    let _ = fs::remove_dir_all(&self.0);
+  }
+}
+
+fn open(dialog: FileDialog, dir: &Path) -> FileDialog {
+  let mut dialog = dialog.async_loading(false);
+  dialog.set_path(dir);
+  dialog.open();
+  dialog
+}
+
+thread_local! {
+  /// Context of the dialog shown by the test on this thread, kept so that focus carries over.
+  static CONTEXT: egui::Context = egui::Context::default();
+}
+
+/// Shows the dialog for one frame, pressing `key` if given, and returns whether it was selected in
+/// that frame.
+fn show_frame(dialog: &mut FileDialog, key: Option<egui::Key>) -> bool {
+  let mut input = egui::RawInput::default();
+  if let Some(key) = key {
+    input.events.push(egui::Event::Key {
+      key,
+      physical_key: None,
+      pressed: true,
+      repeat: false,
+      modifiers: egui::Modifiers::NONE,
+    });
+  }
+
+  let mut selected = false;
+  let ctx = CONTEXT.with(Clone::clone);
+  let _ = ctx.run(input, |ctx| {
+    selected = dialog.show(ctx).selected();
+  });
+  selected
+}
+
+/// Shows the dialog and returns whether `text` was painted. The first frame of a window only
+/// measures it, so two are shown.
+fn shows_text(dialog: &mut FileDialog, text: &str) -> bool {
+  fn contains(shape: &egui::Shape, text: &str) -> bool {
+    match shape {
+      egui::Shape::Text(shape) => shape.galley.text() == text,
+      egui::Shape::Vec(shapes) => shapes.iter().any(|shape| contains(shape, text)),
+      _ => false,
+    }
+  }
+
+  let ctx = CONTEXT.with(Clone::clone);
+  let mut run = || {
+    ctx.run(egui::RawInput::default(), |ctx| {
+      dialog.show(ctx);
+    })
+  };
+  run();
+  run()
+    .shapes
+    .iter()
+    .any(|clipped| contains(&clipped.shape, text))
+}
+
+fn index_of(dialog: &FileDialog, name: &str) -> usize {
+  dialog
+    .current_entries()
+    .unwrap()
+    .iter()
+    .position(|info| info.path().file_name().unwrap() == name)
+    .unwrap()
+}
+
+#[test]
+fn lists_folder() {
+  let dir = TempDir::new("lists", &["b.txt", "a.txt"]);
+  fs::create_dir(dir.0.join("sub")).unwrap();
+  let dialog = open(FileDialog::open_file(None), &dir.0);
+
+  let names: Vec<_> = dialog
+    .current_entries()
+    .unwrap()
+    .iter()
+    .map(|info| info.path().file_name().unwrap().to_owned())
+    .collect();
+  assert_eq!(names, ["sub", "a.txt", "b.txt"]);
+}
+
+#[test]
+fn multi_select_range() {
+  let dir = TempDir::new("range", &["a.txt", "b.txt", "c.txt", "d.txt", "e.txt"]);
+  let mut dialog = open(FileDialog::open_file(None).multi_select(true), &dir.0);
+
+  dialog.apply_command(Command::MultiSelect(index_of(&dialog, "b.txt")));
+  dialog.apply_command(Command::MultiSelectRange(index_of(&dialog, "d.txt")));
+
+  let expected: Vec<_> = ["b.txt", "c.txt", "d.txt"]
+    .iter()
+    .map(|name| dir.0.join(name))
+    .collect();
+  assert_eq!(dialog.selection(), expected);
+
+  dialog.apply_command(Command::ClearSelection);
+  assert!(dialog.selection().is_empty());
+}
+
+#[test]
+fn set_selection_limits() {
+  let dir = TempDir::new("set_selection", &["a.txt", "b.txt", "c.txt"]);
+  let paths: Vec<_> = ["a.txt", "b.txt", "c.txt"]
+    .iter()
+    .map(|name| dir.0.join(name))
+    .collect();
+
+  let dialog = FileDialog::open_file(None)
+    .multi_select(true)
+    .selection_limits(0, Some(2));
+  let mut dialog = open(dialog, &dir.0);
+  dialog.set_selection(paths.clone());
+  assert_eq!(dialog.selection(), &paths[..2]);
+
+  let mut dialog = open(FileDialog::open_file(None), &dir.0);
+  dialog.set_selection(paths.clone());
+  assert_eq!(dialog.path(), Some(paths[0].as_path()));
+}
+
+#[test]
+fn rename() {
+  let dir = TempDir::new("rename", &["old.txt"]);
+  let mut dialog = open(FileDialog::open_file(None), &dir.0);
+
+  let from = dir.0.join("old.txt");
+  let to = dir.0.join("new.txt");
+  dialog.apply_command(Command::Rename(from.clone(), to.clone()));
+
+  assert!(!from.exists());
+  assert!(to.exists());
+  let entries = dialog.current_entries().unwrap();
+  assert_eq!(entries.len(), 1);
+  assert_eq!(entries[0].path(), to);
+  assert_eq!(dialog.path(), Some(to.as_path()));
+}
+
+#[test]
+fn up_directory() {
+  let dir = TempDir::new("up", &[]);
+  let sub = dir.0.join("sub");
+  fs::create_dir(&sub).unwrap();
+  let mut dialog = open(FileDialog::select_folder(None), &sub);
+  assert_eq!(dialog.directory(), sub);
+
+  dialog.apply_command(Command::UpDirectory);
+  assert_eq!(dialog.directory(), dir.0);
+  assert_eq!(index_of(&dialog, "sub"), 0);
+}
+
+#[test]
+fn owned_selection() {
+  let dir = TempDir::new("owned", &["a.txt", "b.txt"]);
+  let mut dialog = open(FileDialog::open_file(None).multi_select(true), &dir.0);
+
+  dialog.apply_command(Command::MultiSelect(index_of(&dialog, "a.txt")));
+  dialog.apply_command(Command::MultiSelectSwitch(index_of(&dialog, "b.txt")));
+  let selection = dialog.selection_owned();
+  drop(dialog);
+
+  assert_eq!(selection, [dir.0.join("a.txt"), dir.0.join("b.txt")]);
+}
+
+#[test]
+fn selected_info() {
+  let dir = TempDir::new("info", &["a.txt"]);
+  fs::write(dir.0.join("a.txt"), b"hello").unwrap();
+  let mut dialog = open(FileDialog::open_file(None), &dir.0);
+
+  let entry = dialog.current_entries().unwrap()[0].clone();
+  dialog.apply_command(Command::Select(entry));
+  let info = dialog.selected_info().unwrap();
+  assert!(info.is_file());
+  assert_eq!(info.size(), 5);
+
+  let path = dialog.path_owned();
+  drop(dialog);
+  assert_eq!(path, Some(dir.0.join("a.txt")));
+}
+
+#[test]
+fn confirm_cancel() {
+  let dir = TempDir::new("cancel", &[]);
+  let mut dialog = open(FileDialog::save_file(None).confirm_cancel(true), &dir.0);
+  dialog.apply_command(Command::Cancel);
+  assert_eq!(dialog.state(), State::Cancelled);
+
+  let dialog = FileDialog::save_file(None)
+    .confirm_cancel(true)
+    .default_filename("a.txt");
+  let mut dialog = open(dialog, &dir.0);
+  dialog.apply_command(Command::Cancel);
+  assert_eq!(dialog.state(), State::Open);
+}
+
+#[test]
+fn create_directory() {
+  let dir = TempDir::new("mkdir", &[]);
+  let mut dialog = open(FileDialog::select_folder(None), &dir.0);
+
+  let path = dir.0.join("new");
+  dialog.apply_command(Command::CreateDirectory(path.clone()));
+  assert!(path.is_dir());
+  assert_eq!(dialog.path(), Some(path.as_path()));
+}
+
+#[test]
+fn missing_initial_path() {
+  let dir = TempDir::new("missing", &[]);
+  let path = dir.0.join("gone").join("deeper").join("file.txt");
+  let mut dialog = FileDialog::save_file(Some(path));
+  dialog.open();
+
+  assert_eq!(dialog.directory(), dir.0);
+}
+
+/// File system that can't list folders named "locked".
+struct Locked;
+
+impl FileSystem for Locked {
+  fn read_dir(&self, path: &Path, lazy_metadata: bool) -> Result<Vec<FileInfo>, Error> {
+    match path.ends_with("locked") {
+      true => Err(Error::from(ErrorKind::PermissionDenied)),
+      false => StdFileSystem.read_dir(path, lazy_metadata),
+    }
+  }
+
+  fn entry(&self, path: &Path) -> FileInfo {
+    StdFileSystem.entry(path)
+  }
+
+  fn create_dir(&self, path: &Path) -> Result<(), Error> {
+    StdFileSystem.create_dir(path)
+  }
+
+  fn rename(&self, from: &Path, to: &Path) -> Result<(), Error> {
+    StdFileSystem.rename(from, to)
+  }
+
+  fn remove(&self, path: &Path) -> Result<(), Error> {
+    StdFileSystem.remove(path)
+  }
+}
+
+#[test]
+fn permission_denied() {
+  let dir = TempDir::new("denied", &[]);
+  let locked = dir.0.join("locked");
+  fs::create_dir(&locked).unwrap();
+  let mut dialog = open(FileDialog::select_folder(None).file_system(Locked), &dir.0);
+
+  dialog.set_path(&locked);
+  let err = dialog.current_entries().unwrap_err();
+  assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+
+  dialog.apply_command(Command::GoBack);
+  assert_eq!(dialog.directory(), dir.0);
+  assert!(dialog.current_entries().is_ok());
+}
+
+#[test]
+fn async_permission_denied() {
+  let dir = TempDir::new("async_denied", &[]);
+  let locked = dir.0.join("locked");
+  fs::create_dir(&locked).unwrap();
+  let dialog = FileDialog::select_folder(None).file_system(Locked);
+  let mut dialog = open(dialog, &dir.0).async_loading(true);
+
+  dialog.apply_command(Command::BrowseDirectory(StdFileSystem.entry(&locked)));
+  while dialog.is_loading() {
+    show_frame(&mut dialog, None);
+  }
+  assert_eq!(dialog.directory(), dir.0);
+  assert!(dialog.take_error().is_some());
+}
+
+#[test]
+fn dirs_filter() {
+  let dir = TempDir::new("dirs", &["a.txt"]);
+  fs::create_dir(dir.0.join("node_modules")).unwrap();
+  fs::create_dir(dir.0.join("src")).unwrap();
+  let filter = Box::new(|path: &Path| !path.ends_with("node_modules"));
+  let dialog = open(FileDialog::open_file(None).show_dirs_filter(filter), &dir.0);
+
+  let entries = dialog.current_entries().unwrap();
+  let paths: Vec<_> = entries.iter().map(|info| info.path()).collect();
+  assert_eq!(paths, [dir.0.join("src"), dir.0.join("a.txt")]);
+}
+
+#[test]
+fn unique_on_conflict() {
+  let dir = TempDir::new("unique", &["report.txt", "report (1).txt"]);
+  let mut dialog = open(FileDialog::save_file(None).unique_on_conflict(true), &dir.0);
+
+  let idx = index_of(&dialog, "report.txt");
+  let entry = dialog.current_entries().unwrap()[idx].clone();
+  dialog.apply_command(Command::Save(entry));
+  assert_eq!(dialog.path(), Some(dir.0.join("report (2).txt").as_path()));
+}
+
+#[test]
+fn read_only() {
+  let dir = TempDir::new("read_only", &["locked.txt", "open.txt"]);
+  let path = dir.0.join("locked.txt");
+  let mut permissions = fs::metadata(&path).unwrap().permissions();
+  permissions.set_readonly(true);
+  fs::set_permissions(&path, permissions).unwrap();
+  let dialog = open(FileDialog::open_file(None), &dir.0);
+
+  let entries = dialog.current_entries().unwrap();
+  assert!(entries[index_of(&dialog, "locked.txt")].is_read_only());
+  assert!(!entries[index_of(&dialog, "open.txt")].is_read_only());
+}
+
+#[test]
+fn save_over_read_only() {
+  let dir = TempDir::new("save_read_only", &["locked.txt"]);
+  let path = dir.0.join("locked.txt");
+  let mut permissions = fs::metadata(&path).unwrap().permissions();
+  permissions.set_readonly(true);
+  fs::set_permissions(&path, permissions).unwrap();
+  let dialog = FileDialog::save_file(None).confirm_overwrite(false);
+  let mut dialog = open(dialog, &dir.0);
+
+  // As if the entry was double-clicked.
+  let entry = dialog.current_entries().unwrap()[0].clone();
+  dialog.apply_command(Command::Save(entry));
+  assert_eq!(dialog.path(), None);
+  assert!(dialog.take_error().is_some());
+}
+
+#[test]
+fn remove_all() {
+  let dir = TempDir::new("remove_all", &["a.txt"]);
+  let sub = dir.0.join("sub");
+  fs::create_dir_all(sub.join("deeper")).unwrap();
+  fs::write(sub.join("deeper").join("b.txt"), b"").unwrap();
+
+  // The default implementation, with the file system's own read_dir and remove.
+  Locked.remove_all(&sub).unwrap();
+  assert!(!sub.exists());
+  assert!(dir.0.join("a.txt").exists());
+}
+
+#[test]
+fn enter_path() {
+  let dir = TempDir::new("enter_path", &["a.txt"]);
+  let sub = dir.0.join("sub");
+  fs::create_dir(&sub).unwrap();
+  let file = dir.0.join("a.txt");
+
+  // A file goes to its folder in folder dialogs.
+  let mut dialog = open(FileDialog::select_folder(None), &sub);
+  dialog.apply_command(Command::EnterPath(file.clone()));
+  assert_eq!(dialog.directory(), dir.0);
+  assert_eq!(dialog.state(), State::Open);
+
+  // And is selected in save dialogs.
+  let mut dialog = open(FileDialog::save_file(None), &sub);
+  dialog.apply_command(Command::EnterPath(file.clone()));
+  assert_eq!(dialog.directory(), dir.0);
+  assert_eq!(dialog.path(), Some(file.as_path()));
+
+  // Paths that don't exist go to the nearest existing folder.
+  dialog.apply_command(Command::EnterPath(sub.join("gone").join("deeper")));
+  assert_eq!(dialog.directory(), sub);
+
+  dialog.apply_command(Command::EnterPath(dir.0.clone()));
+  assert_eq!(dialog.directory(), dir.0);
+}
+
+#[test]
+fn take_confirmed() {
+  let dir = TempDir::new("take_confirmed", &["a.txt"]);
+  let mut dialog = open(FileDialog::open_file(None), &dir.0);
+  let ctx = egui::Context::default();
+
+  let entry = dialog.current_entries().unwrap()[0].clone();
+  dialog.apply_command(Command::Open(entry));
+  assert_eq!(dialog.take_confirmed(), None);
+
+  let _ = ctx.run(Default::default(), |ctx| {
+    dialog.show(ctx);
+  });
+  assert!(dialog.selected());
+  assert_eq!(dialog.take_confirmed(), Some(vec![dir.0.join("a.txt")]));
+  assert_eq!(dialog.take_confirmed(), None);
+  assert!(dialog.selected());
+}
+
+#[test]
+fn delete_selection_with_search() {
+  let dir = TempDir::new("delete_search", &["keep.txt", "drop.txt", "other.txt"]);
+  let mut dialog = open(FileDialog::open_file(None).multi_select(true), &dir.0);
+
+  dialog.apply_command(Command::SelectAll);
+  dialog.apply_command(Command::Search("drop".to_string()));
+  assert_eq!(dialog.selection(), [dir.0.join("drop.txt")]);
+
+  dialog.apply_command(Command::DeleteSelection);
+  dialog.apply_command(Command::AnswerPrompt(true));
+  assert!(!dir.0.join("drop.txt").exists());
+  assert!(dir.0.join("keep.txt").exists());
+  assert!(dir.0.join("other.txt").exists());
+}
+
+#[test]
+fn confirm_in_same_frame() {
+  let dir = TempDir::new("same_frame", &["a.txt"]);
+  let mut dialog = open(FileDialog::open_file(None), &dir.0);
+
+  let entry = dialog.current_entries().unwrap()[0].clone();
+  dialog.apply_command(Command::Select(entry));
+  assert!(!show_frame(&mut dialog, None));
+  assert!(show_frame(&mut dialog, Some(egui::Key::Enter)));
+  assert_eq!(dialog.path(), Some(dir.0.join("a.txt").as_path()));
+}
+
+/// Dialog with a folder "sub" and the files "a.txt" and "b.md", with "a.txt" highlighted.
+fn enter_dialog(name: &str, dialog: FileDialog) -> (TempDir, FileDialog) {
+  let dir = TempDir::new(name, &["a.txt", "b.md"]);
+  fs::create_dir(dir.0.join("sub")).unwrap();
+  let filter = Box::new(|name: &str| name.ends_with(".txt"));
+  let mut dialog = open(dialog.filename_filter(filter), &dir.0);
+  show_frame(&mut dialog, None);
+  (dir, dialog)
+}
+
+fn highlight(dialog: &mut FileDialog, name: &str) {
+  let entry = dialog.current_entries().unwrap()[index_of(dialog, name)].clone();
+  dialog.apply_command(Command::Select(entry));
+}
+
+#[test]
+fn enter_select_folder() {
+  let (dir, mut dialog) = enter_dialog("enter_folder", FileDialog::select_folder(None));
+
+  // Files can't be chosen.
+  highlight(&mut dialog, "a.txt");
+  assert!(!show_frame(&mut dialog, Some(egui::Key::Enter)));
+  assert_eq!(dialog.directory(), dir.0);
+
+  highlight(&mut dialog, "sub");
+  assert!(!show_frame(&mut dialog, Some(egui::Key::Enter)));
+  assert_eq!(dialog.directory(), dir.0.join("sub"));
+}
+
+#[test]
+fn enter_open_file() {
+  let (dir, mut dialog) = enter_dialog("enter_open", FileDialog::open_file(None));
+
+  highlight(&mut dialog, "b.md");
+  assert!(!show_frame(&mut dialog, Some(egui::Key::Enter)));
+
+  highlight(&mut dialog, "a.txt");
+  assert!(show_frame(&mut dialog, Some(egui::Key::Enter)));
+  assert_eq!(dialog.path(), Some(dir.0.join("a.txt").as_path()));
+
+  let (dir, mut dialog) = enter_dialog("enter_open_sub", FileDialog::open_file(None));
+  highlight(&mut dialog, "sub");
+  assert!(!show_frame(&mut dialog, Some(egui::Key::Enter)));
+  assert_eq!(dialog.directory(), dir.0.join("sub"));
+}
+
+#[test]
+fn keys_need_list_focus() {
+  let (dir, mut dialog) = enter_dialog("list_focus", FileDialog::open_file(None));
+  highlight(&mut dialog, "a.txt");
+
+  // Another widget of the app has the focus.
+  let host = egui::Id::new("host");
+  CONTEXT.with(|ctx| ctx.memory_mut(|mem| mem.request_focus(host)));
+  assert!(!show_frame(&mut dialog, Some(egui::Key::Enter)));
+
+  CONTEXT.with(|ctx| ctx.memory_mut(|mem| mem.surrender_focus(host)));
+  dialog.open();
+  highlight(&mut dialog, "a.txt");
+  assert!(show_frame(&mut dialog, Some(egui::Key::Enter)));
+  assert_eq!(dialog.path(), Some(dir.0.join("a.txt").as_path()));
+}
+
+#[test]
+fn enter_save_file() {
+  let dialog = FileDialog::save_file(None).confirm_overwrite(false);
+  let (dir, mut dialog) = enter_dialog("enter_save", dialog);
+
+  highlight(&mut dialog, "b.md");
+  assert!(!show_frame(&mut dialog, Some(egui::Key::Enter)));
+
+  highlight(&mut dialog, "a.txt");
+  assert!(show_frame(&mut dialog, Some(egui::Key::Enter)));
+  assert_eq!(dialog.path(), Some(dir.0.join("a.txt").as_path()));
+
+  let dialog = FileDialog::save_file(None).confirm_overwrite(false);
+  let (dir, mut dialog) = enter_dialog("enter_save_sub", dialog);
+  highlight(&mut dialog, "sub");
+  assert!(!show_frame(&mut dialog, Some(egui::Key::Enter)));
+  assert_eq!(dialog.directory(), dir.0.join("sub"));
+}
+
+#[test]
+fn trim_filename() {
+  let dir = TempDir::new("trim", &[]);
+  let dialog = FileDialog::save_file(None).default_filename(" report.txt  ");
+  let mut dialog = open(dialog, &dir.0);
+  assert!(shows_text(
+    &mut dialog,
+    "Leading and trailing spaces will be removed"
+  ));
+
+  dialog.apply_command(Command::SaveTyped);
+  assert_eq!(dialog.path(), Some(dir.0.join("report.txt").as_path()));
+
+  let dialog = FileDialog::save_file(None)
+    .default_filename(" report.txt")
+    .trim_filename(false);
+  let mut dialog = open(dialog, &dir.0);
+  assert!(!shows_text(
+    &mut dialog,
+    "Leading and trailing spaces will be removed"
+  ));
+  dialog.apply_command(Command::SaveTyped);
+  assert_eq!(dialog.path(), Some(dir.0.join(" report.txt").as_path()));
+}
+
+#[cfg(windows)]
+#[test]
+fn trailing_dot_warning() {
+  let dir = TempDir::new("trailing_dot", &[]);
+  let mut dialog = open(
+    FileDialog::save_file(None).default_filename("report."),
+    &dir.0,
+  );
+  assert!(shows_text(
+    &mut dialog,
+    "Trailing dots will be removed by Windows"
+  ));
+}
+
+#[cfg(unix)]
+#[test]
+fn follow_symlinks() {
+  let dir = TempDir::new("follow_symlinks", &["target.txt"]);
+  let link = dir.0.join("link.txt");
+  std::os::unix::fs::symlink(dir.0.join("target.txt"), &link).unwrap();
+  let dialog = FileDialog::open_file(None)
+    .multi_select(true)
+    .follow_symlinks(true);
+  let mut dialog = open(dialog, &dir.0);
+
+  dialog.apply_command(Command::MultiSelect(index_of(&dialog, "link.txt")));
+  assert_eq!(dialog.selection(), [link.as_path()]);
+
+  dialog.apply_command(Command::OpenSelected);
+  let target = fs::canonicalize(dir.0.join("target.txt")).unwrap();
+  assert_eq!(dialog.selection(), [target.as_path()]);
+  // The listing keeps the path of the link.
+  let entries = dialog.current_entries().unwrap();
+  assert_eq!(entries[index_of(&dialog, "link.txt")].path(), link);
+}
+
+#[test]
+fn suggest_save_copy() {
+  let dir = TempDir::new("save_copy", &["notes.txt", "notes copy.txt"]);
+  let mut dialog = open(FileDialog::save_file(None), &dir.0);
+
+  // A bare file name is looked up in the current folder.
+  dialog.suggest_save_copy(Path::new("notes.txt"));
+  assert_eq!(dialog.directory(), dir.0);
+  assert!(dialog.current_entries().is_ok());
+
+  dialog.apply_command(Command::SaveTyped);
+  assert_eq!(
+    dialog.path(),
+    Some(dir.0.join("notes copy 2.txt").as_path())
+  );
+}
+
+/// File system with only the folder "/virtual" and the file "/virtual/data" in it.
+struct Virtual;
+
+impl FileSystem for Virtual {
+  fn read_dir(&self, path: &Path, _lazy_metadata: bool) -> Result<Vec<FileInfo>, Error> {
+    match path == Path::new("/virtual") {
+      true => Ok(vec![self.entry(&path.join("data"))]),
+      false => Err(Error::from(ErrorKind::NotFound)),
+    }
+  }
+
+  fn entry(&self, path: &Path) -> FileInfo {
+    match path.to_str() {
+      Some("/virtual") => FileInfo::virtual_dir(path),
+      Some("/virtual/data") => FileInfo::virtual_file(path, 4, None),
+      _ => FileInfo::default(),
+    }
+  }
+
+  fn create_dir(&self, _path: &Path) -> Result<(), Error> {
+    Err(Error::from(ErrorKind::Unsupported))
+  }
+
+  fn rename(&self, _from: &Path, _to: &Path) -> Result<(), Error> {
+    Err(Error::from(ErrorKind::Unsupported))
+  }
+
+  fn remove(&self, _path: &Path) -> Result<(), Error> {
+    Err(Error::from(ErrorKind::Unsupported))
+  }
+}
+
+#[test]
+fn virtual_initial_file() {
+  let dialog = FileDialog::save_file(Some(PathBuf::from("/virtual/data"))).file_system(Virtual);
+  let mut dialog = dialog.async_loading(false).confirm_overwrite(false);
+  dialog.open();
+  assert_eq!(dialog.directory(), Path::new("/virtual"));
+
+  dialog.apply_command(Command::SaveTyped);
+  assert_eq!(dialog.path(), Some(Path::new("/virtual/data")));
+}
+
+#[cfg(feature = "zip")]
+#[test]
+fn zip_archive() {
+  use egui_file::ZipFileSystem;
+  use std::io::Write;
+
+  let dir = TempDir::new("zip", &[]);
+  let archive = dir.0.join("data.zip");
+  let mut zip = zip::ZipWriter::new(fs::File::create(&archive).unwrap());
+  let options = zip::write::SimpleFileOptions::default();
+  zip.start_file("sub/inner.txt", options).unwrap();
+  zip.write_all(b"inner").unwrap();
+  zip.start_file("top.txt", options).unwrap();
+  zip.add_directory("empty/", options).unwrap();
+  zip.finish().unwrap();
+
+  let file_system = ZipFileSystem::default();
+  let names: Vec<_> = file_system
+    .read_dir(&archive, false)
+    .unwrap()
+    .iter()
+    .map(|info| (info.path().file_name().unwrap().to_owned(), info.is_dir()))
+    .collect();
+  assert_eq!(
+    names,
+    [
+      ("sub".into(), true),
+      ("top.txt".into(), false),
+      ("empty".into(), true)
+    ]
+  );
+
+  let inner = file_system.entry(&archive.join("sub").join("inner.txt"));
+  assert!(inner.is_file());
+  assert_eq!(inner.size(), 5);
+  assert!(!file_system.exists(&archive.join("missing.txt")));
+  assert!(file_system
+    .read_dir(&archive.join("top.txt"), false)
+    .is_err());
+}